@@ -24,7 +24,7 @@ fn build_dawg(lines: &[String]) -> Dawg {
     lines
         .iter()
         .fold(DawgBuilder::new(), |mut builder, line| {
-            builder.insert_key(line, 1);
+            builder.insert_key(line, 1).unwrap();
             builder
         })
         .build()
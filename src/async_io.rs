@@ -0,0 +1,100 @@
+use crate::dictionary::{Dictionary, DictionaryUnit, LargeDictionaryUnit, Unit, UnitStorage};
+use crate::header::{self, Header};
+use std::convert::TryFrom;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+// Units are written and read in chunks of this many bytes
+const CHUNK_SIZE: usize = 64 * 1024;
+
+impl Dictionary {
+    /// Asynchronous counterpart of `from_reader`, accepting the same formats.
+    ///
+    /// Reads exactly the bytes of the dictionary, so whatever follows it in the stream stays
+    /// available to the caller, then parses them with `from_reader`.
+    pub async fn from_async_reader<T: AsyncRead + Unpin>(reader: &mut T) -> Option<Self> {
+        let mut bytes = Vec::new();
+        read_bytes(reader, &mut bytes, 4).await?;
+        let mut unit_size = DictionaryUnit::SIZE;
+        if bytes[..] == header::MAGIC {
+            read_bytes(reader, &mut bytes, Header::SIZE - 4).await?;
+            let header = Header::from_bytes(&bytes)?;
+            if header.flags & header::FLAG_LARGE_UNITS != 0 {
+                unit_size = LargeDictionaryUnit::SIZE;
+            }
+            #[cfg(feature = "compression")]
+            if header.flags & header::FLAG_COMPRESSED != 0 {
+                // Unit count, then the length of the compressed units
+                read_bytes(reader, &mut bytes, 8).await?;
+                let compressed_len = usize::try_from(last_u32(&bytes)).ok()?;
+                read_bytes(reader, &mut bytes, compressed_len).await?;
+                return Dictionary::from_reader(&mut bytes.as_slice());
+            }
+        }
+        read_bytes(reader, &mut bytes, 4).await?;
+        let units_len = usize::try_from(last_u32(&bytes))
+            .ok()?
+            .checked_mul(unit_size)?;
+        read_bytes(reader, &mut bytes, units_len).await?;
+        Dictionary::from_reader(&mut bytes.as_slice())
+    }
+}
+
+impl<U: UnitStorage> Dictionary<U> {
+    /// Asynchronous counterpart of `write`.
+    pub async fn write_async<T: AsyncWrite + Unpin>(&self, writer: &mut T) -> std::io::Result<()> {
+        if U::Unit::HEADER_FLAGS != 0 {
+            return self.write_with_header_async(writer).await;
+        }
+        self.write_units_async(writer).await
+    }
+
+    /// Asynchronous counterpart of `write_with_header`.
+    pub async fn write_with_header_async<T: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut T,
+    ) -> std::io::Result<()> {
+        let mut header_bytes = Vec::with_capacity(Header::SIZE);
+        self.header().write(&mut header_bytes)?;
+        writer.write_all(&header_bytes).await?;
+        self.write_units_async(writer).await
+    }
+
+    async fn write_units_async<T: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut T,
+    ) -> std::io::Result<()> {
+        writer.write_u32_le(self.size()).await?;
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+        for index in 0..self.size() {
+            let unit = self.units().get(index).unwrap_or_default();
+            buffer.extend_from_slice(&unit.to_bits().to_le_bytes()[..U::Unit::SIZE]);
+            if buffer.len() >= CHUNK_SIZE {
+                writer.write_all(&buffer).await?;
+                buffer.clear();
+            }
+        }
+        writer.write_all(&buffer).await
+    }
+}
+
+// Appends exactly `len` bytes to `bytes`. `len` comes from the data, so `bytes` grows chunk by
+// chunk as they arrive rather than all at once
+async fn read_bytes<T: AsyncRead + Unpin>(
+    reader: &mut T,
+    bytes: &mut Vec<u8>,
+    len: usize,
+) -> Option<()> {
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk_len = remaining.min(CHUNK_SIZE);
+        let start = bytes.len();
+        bytes.resize(start + chunk_len, 0);
+        reader.read_exact(&mut bytes[start..]).await.ok()?;
+        remaining -= chunk_len;
+    }
+    Some(())
+}
+
+fn last_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[bytes.len() - 4..].try_into().unwrap())
+}
@@ -0,0 +1,237 @@
+use crate::dictionary::{Dictionary, DictionaryUnit, UnitStorage};
+use crate::pool::Pool;
+use crate::unit::BaseType;
+use std::ops::{Bound, RangeBounds};
+
+/// A byte-level acceptor that can be intersected with a `Dictionary`.
+///
+/// The traversal feeds key bytes one by one starting from `start`, and prunes
+/// a branch as soon as `can_match` reports that no continuation can match.
+pub trait Automaton {
+    type State: Clone;
+
+    fn start(&self) -> Self::State;
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State;
+
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    fn can_match(&self, state: &Self::State) -> bool;
+}
+
+impl<A: Automaton> Automaton for &A {
+    type State = A::State;
+
+    fn start(&self) -> Self::State {
+        (*self).start()
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        (*self).accept(state, byte)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        (*self).is_match(state)
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        (*self).can_match(state)
+    }
+}
+
+/// Matches every key.
+#[derive(Copy, Clone, Default)]
+pub struct AlwaysMatch;
+
+impl Automaton for AlwaysMatch {
+    type State = ();
+
+    fn start(&self) {}
+
+    fn accept(&self, _state: &(), _byte: u8) {}
+
+    fn is_match(&self, _state: &()) -> bool {
+        true
+    }
+
+    fn can_match(&self, _state: &()) -> bool {
+        true
+    }
+}
+
+/// Matches keys lying between two byte strings in lexicographic order.
+#[derive(Clone)]
+pub struct KeyRange {
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+}
+
+#[derive(Copy, Clone)]
+pub struct KeyRangeState {
+    len: usize,
+    above_lower: bool,
+    below_upper: bool,
+    is_dead: bool,
+}
+
+impl KeyRange {
+    pub fn new<K: AsRef<[u8]>, R: RangeBounds<K>>(range: R) -> KeyRange {
+        let to_owned = |bound: Bound<&K>| match bound {
+            Bound::Included(key) => Bound::Included(key.as_ref().to_vec()),
+            Bound::Excluded(key) => Bound::Excluded(key.as_ref().to_vec()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        KeyRange {
+            lower: to_owned(range.start_bound()),
+            upper: to_owned(range.end_bound()),
+        }
+    }
+}
+
+impl Automaton for KeyRange {
+    type State = KeyRangeState;
+
+    fn start(&self) -> KeyRangeState {
+        KeyRangeState {
+            len: 0,
+            above_lower: matches!(self.lower, Bound::Unbounded),
+            below_upper: matches!(self.upper, Bound::Unbounded),
+            is_dead: false,
+        }
+    }
+
+    fn accept(&self, state: &KeyRangeState, byte: u8) -> KeyRangeState {
+        let mut next = *state;
+        next.len += 1;
+        if next.is_dead {
+            return next;
+        }
+
+        if let (false, Bound::Included(lower) | Bound::Excluded(lower)) =
+            (state.above_lower, &self.lower)
+        {
+            match lower.get(state.len) {
+                Some(&label) if byte < label => next.is_dead = true,
+                Some(&label) if byte == label => (),
+                _ => next.above_lower = true,
+            }
+        }
+
+        if let (false, Bound::Included(upper) | Bound::Excluded(upper)) =
+            (state.below_upper, &self.upper)
+        {
+            match upper.get(state.len) {
+                Some(&label) if byte < label => next.below_upper = true,
+                Some(&label) if byte == label => (),
+                _ => next.is_dead = true,
+            }
+        }
+
+        next
+    }
+
+    fn is_match(&self, state: &KeyRangeState) -> bool {
+        if state.is_dead {
+            return false;
+        }
+        // Without crossing a bound the key is still a prefix of that bound
+        let fits_lower = state.above_lower
+            || match &self.lower {
+                Bound::Included(lower) => state.len == lower.len(),
+                Bound::Excluded(_) | Bound::Unbounded => false,
+            };
+        let fits_upper = state.below_upper
+            || match &self.upper {
+                Bound::Included(upper) => state.len <= upper.len(),
+                Bound::Excluded(upper) => state.len < upper.len(),
+                Bound::Unbounded => true,
+            };
+        fits_lower && fits_upper
+    }
+
+    fn can_match(&self, state: &KeyRangeState) -> bool {
+        !state.is_dead
+    }
+}
+
+// Search
+
+/// Stream of `(key, value)` pairs accepted by an automaton, in lexicographic order.
+pub struct Search<'a, A: Automaton, U = Pool<DictionaryUnit>> {
+    dictionary: &'a Dictionary<U>,
+    automaton: A,
+    stack: Vec<SearchFrame<A::State>>,
+    key: Vec<u8>,
+}
+
+struct SearchFrame<S> {
+    index: BaseType,
+    state: S,
+    // 0 means the leaf of this state is yet to be checked, 1..=255 is the next label to follow.
+    next_label: u16,
+}
+
+impl<'a, A: Automaton, U: UnitStorage> Search<'a, A, U> {
+    pub(crate) fn new(dictionary: &'a Dictionary<U>, automaton: A) -> Search<'a, A, U> {
+        let state = automaton.start();
+        let mut stack = Vec::new();
+        if automaton.can_match(&state) {
+            stack.push(SearchFrame {
+                index: dictionary.root(),
+                state,
+                next_label: 0,
+            });
+        }
+        Search {
+            dictionary,
+            automaton,
+            stack,
+            key: Vec::new(),
+        }
+    }
+}
+
+impl<A: Automaton, U: UnitStorage> Iterator for Search<'_, A, U> {
+    type Item = (Vec<u8>, BaseType);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+
+            if frame.next_label == 0 {
+                frame.next_label = 1;
+                if self.dictionary.has_value(frame.index) && self.automaton.is_match(&frame.state) {
+                    if let Some(value) = self.dictionary.value(frame.index) {
+                        return Some((self.key.clone(), value));
+                    }
+                }
+                continue;
+            }
+
+            if frame.next_label > 0xFF {
+                self.stack.pop();
+                self.key.pop();
+                continue;
+            }
+
+            let label = frame.next_label as u8;
+            frame.next_label += 1;
+
+            if !self.dictionary.can_extend_key(self.key.len()) {
+                continue;
+            }
+
+            if let Some(child_index) = self.dictionary.follow(label, frame.index) {
+                let state = self.automaton.accept(&frame.state, label);
+                if self.automaton.can_match(&state) {
+                    self.key.push(label);
+                    self.stack.push(SearchFrame {
+                        index: child_index,
+                        state,
+                        next_label: 0,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// Standard base64 alphabet, as produced by Python's `binascii.b2a_base64`
+
+/// Decodes `input`, skipping padding and line breaks.
+pub(crate) fn decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in input {
+        let sextet = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' | b'\n' | b'\r' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(sextet);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(output)
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `input` with padding and a trailing line break, like `binascii.b2a_base64`.
+pub(crate) fn encode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len().div_ceil(3) * 4 + 1);
+    for chunk in input.chunks(3) {
+        let mut bytes = [0; 3];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let buffer = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for position in 0..4 {
+            if position <= chunk.len() {
+                output.push(ALPHABET[((buffer >> (18 - 6 * position)) & 0x3F) as usize]);
+            } else {
+                output.push(b'=');
+            }
+        }
+    }
+    output.push(b'\n');
+    output
+}
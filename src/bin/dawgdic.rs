@@ -0,0 +1,297 @@
+//! Builds dictionaries from `key\tvalue` files and inspects existing dictionary files.
+
+use clap::{Parser, Subcommand};
+use dawgdic::automaton::AlwaysMatch;
+use dawgdic::dawg::DawgBuilder;
+use dawgdic::dictionary::{Dictionary, DictionaryBuilder};
+use dawgdic::guide::{Completer, GuideBuilder};
+use dawgdic::unit::BaseType;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+// Values share their unit with the leaf flag
+const MAX_VALUE: BaseType = (1 << 31) - 1;
+
+#[derive(Parser)]
+#[command(
+    name = "dawgdic",
+    version,
+    about = "Builds and queries DAWG dictionaries"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Builds a dictionary from lines of `key<TAB>value`, sorted bytewise (as by `LC_ALL=C sort`).
+    ///
+    /// Lines without a tab are keys with the value 0.
+    Build {
+        input: PathBuf,
+        output: PathBuf,
+        /// Precedes the dictionary with a header holding its key count and checksum
+        #[arg(long)]
+        header: bool,
+        /// Stores the dictionary deflated; implies a header
+        #[cfg(feature = "compression")]
+        #[arg(long)]
+        compressed: bool,
+    },
+    /// Prints `key<TAB>value` for every given key in the dictionary.
+    ///
+    /// Keys are read from standard input, one per line, when none are given.
+    /// Exits with 1 if any key is missing.
+    Find {
+        dictionary: PathBuf,
+        keys: Vec<String>,
+    },
+    /// Prints `key<TAB>true` or `key<TAB>false` for every given key.
+    ///
+    /// Keys are read from standard input, one per line, when none are given.
+    /// Exits with 1 if any key is missing.
+    Contains {
+        dictionary: PathBuf,
+        keys: Vec<String>,
+    },
+    /// Prints the keys that are prefixes of `query`, shortest first.
+    Prefix { dictionary: PathBuf, query: String },
+    /// Prints the keys starting with `prefix`, in lexicographic order.
+    Complete {
+        dictionary: PathBuf,
+        prefix: String,
+        /// Stops after this many keys
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Prints every key of the dictionary in the format `build` reads.
+    Dump { dictionary: PathBuf },
+    /// Prints the size and layout metrics of the dictionary.
+    Stats { dictionary: PathBuf },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("dawgdic: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(command: Command) -> Result<ExitCode, String> {
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let code = match command {
+        Command::Build {
+            input,
+            output,
+            header,
+            #[cfg(feature = "compression")]
+            compressed,
+        } => {
+            let dictionary = build(&input)?;
+            let file = File::create(&output).map_err(|error| describe(&output, error))?;
+            let mut writer = BufWriter::new(file);
+            #[cfg(feature = "compression")]
+            let result = if compressed {
+                dictionary.write_compressed(&mut writer)
+            } else if header {
+                dictionary.write_with_header(&mut writer)
+            } else {
+                dictionary.write(&mut writer)
+            };
+            #[cfg(not(feature = "compression"))]
+            let result = if header {
+                dictionary.write_with_header(&mut writer)
+            } else {
+                dictionary.write(&mut writer)
+            };
+            result
+                .and_then(|_| writer.flush())
+                .map_err(|error| describe(&output, error))?;
+            writeln!(
+                out,
+                "{} keys, {} units",
+                dictionary.key_count(),
+                dictionary.size()
+            )
+            .map_err(|error| error.to_string())?;
+            ExitCode::SUCCESS
+        }
+        Command::Find { dictionary, keys } => {
+            let dictionary = load(&dictionary)?;
+            let mut is_complete = true;
+            for key in keys_or_stdin(keys)? {
+                match dictionary.find(key.as_bytes()) {
+                    Some(value) => writeln!(out, "{}\t{}", key, value),
+                    None => {
+                        is_complete = false;
+                        Ok(())
+                    }
+                }
+                .map_err(|error| error.to_string())?;
+            }
+            exit_code(is_complete)
+        }
+        Command::Contains { dictionary, keys } => {
+            let dictionary = load(&dictionary)?;
+            let mut is_complete = true;
+            for key in keys_or_stdin(keys)? {
+                let contains = dictionary.contains(key.as_bytes());
+                is_complete &= contains;
+                writeln!(out, "{}\t{}", key, contains).map_err(|error| error.to_string())?;
+            }
+            exit_code(is_complete)
+        }
+        Command::Prefix { dictionary, query } => {
+            let dictionary = load(&dictionary)?;
+            let mut index = Some(dictionary.root());
+            for len in 0..=query.len() {
+                let Some(current) = index else { break };
+                if dictionary.has_value(current) {
+                    if let Some(value) = dictionary.value(current) {
+                        out.write_all(&query.as_bytes()[..len])
+                            .and_then(|_| writeln!(out, "\t{}", value))
+                            .map_err(|error| error.to_string())?;
+                    }
+                }
+                index = query
+                    .as_bytes()
+                    .get(len)
+                    .and_then(|&label| dictionary.follow(label, current));
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Complete {
+            dictionary,
+            prefix,
+            limit,
+        } => {
+            let dictionary = load(&dictionary)?;
+            let guide = GuideBuilder::new(&dictionary).build();
+            let mut completer = Completer::new(&dictionary, &guide);
+            if let Some(index) = dictionary.follow_bytes(prefix.as_bytes(), dictionary.root()) {
+                completer.start(index, prefix.as_bytes());
+            }
+            for (key, value) in completer.take(limit.unwrap_or(usize::MAX)) {
+                write_entry(&mut out, &key, value).map_err(|error| error.to_string())?;
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Dump { dictionary } => {
+            let dictionary = load(&dictionary)?;
+            for (key, value) in dictionary.search(AlwaysMatch) {
+                write_entry(&mut out, &key, value).map_err(|error| error.to_string())?;
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Stats { dictionary: path } => {
+            let dictionary = load(&path)?;
+            let file_size = std::fs::metadata(&path)
+                .map_err(|error| describe(&path, error))?
+                .len();
+            let validation = dictionary.validate();
+            let validation_message = match &validation {
+                Ok(()) => "ok".to_string(),
+                Err(error) => error.to_string(),
+            };
+            let stats = dictionary.stats();
+            writeln!(out, "file size\t{}", file_size)
+                .and_then(|_| writeln!(out, "memory footprint\t{}", stats.memory_footprint))
+                .and_then(|_| writeln!(out, "units\t{}", stats.total_units))
+                .and_then(|_| writeln!(out, "used units\t{}", stats.used_units))
+                .and_then(|_| writeln!(out, "unused units\t{}", stats.unused_units))
+                .and_then(|_| writeln!(out, "fill ratio\t{:.4}", stats.fill_ratio))
+                .and_then(|_| writeln!(out, "extension offsets\t{}", stats.extension_offsets))
+                .and_then(|_| writeln!(out, "keys\t{}", stats.key_count))
+                .and_then(|_| writeln!(out, "max key length\t{}", stats.max_key_length))
+                .and_then(|_| writeln!(out, "validation\t{}", validation_message))
+                .map_err(|error| error.to_string())?;
+            exit_code(validation.is_ok())
+        }
+    };
+    out.flush().map_err(|error| error.to_string())?;
+    Ok(code)
+}
+
+fn build(input: &Path) -> Result<Dictionary, String> {
+    let file = File::open(input).map_err(|error| describe(input, error))?;
+    let mut builder = DawgBuilder::new();
+    let mut previous_key: Option<String> = None;
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|error| describe(input, error))?;
+        let location = format!("{}:{}", input.display(), number + 1);
+        let (key, value) = match line.split_once('\t') {
+            Some((key, value)) => {
+                let value = value
+                    .trim_end_matches('\r')
+                    .parse::<BaseType>()
+                    .ok()
+                    .filter(|&value| value <= MAX_VALUE)
+                    .ok_or_else(|| format!("{}: value must be below 2^31", location))?;
+                (key, value)
+            }
+            None => (line.trim_end_matches('\r'), 0),
+        };
+        if key.is_empty() || key.contains('\0') {
+            return Err(format!(
+                "{}: keys must be non-empty and free of NUL",
+                location
+            ));
+        }
+        if previous_key
+            .as_deref()
+            .is_some_and(|previous| previous >= key)
+        {
+            return Err(format!(
+                "{}: keys must be unique and sorted bytewise, e.g. with `LC_ALL=C sort`",
+                location
+            ));
+        }
+        builder
+            .insert_key(key, value)
+            .map_err(|_| format!("{}: could not insert key", location))?;
+        previous_key = Some(key.to_string());
+    }
+    Ok(DictionaryBuilder::new(builder.build()).build())
+}
+
+fn load(path: &Path) -> Result<Dictionary, String> {
+    let file = File::open(path).map_err(|error| describe(path, error))?;
+    Dictionary::from_reader(&mut BufReader::new(file))
+        .ok_or_else(|| format!("{}: not a dictionary, or corrupted", path.display()))
+}
+
+fn keys_or_stdin(keys: Vec<String>) -> Result<Vec<String>, String> {
+    if !keys.is_empty() {
+        return Ok(keys);
+    }
+    std::io::stdin()
+        .lock()
+        .lines()
+        .map(|line| line.map(|line| line.trim_end_matches('\r').to_string()))
+        .collect::<Result<_, _>>()
+        .map_err(|error| error.to_string())
+}
+
+fn write_entry<T: Write>(out: &mut T, key: &[u8], value: BaseType) -> std::io::Result<()> {
+    out.write_all(key)?;
+    writeln!(out, "\t{}", value)
+}
+
+fn exit_code(is_success: bool) -> ExitCode {
+    if is_success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn describe(path: &Path, error: std::io::Error) -> String {
+    format!("{}: {}", path.display(), error)
+}
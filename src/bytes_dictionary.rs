@@ -0,0 +1,137 @@
+//! Keys mapped to any number of byte strings.
+//!
+//! Every value is stored as a key of its own, `key + separator + base64(value)`, the way
+//! the Python `DAWG` package does it. Files are those of its `BytesDAWG`.
+
+use crate::base64;
+use crate::dawg::DawgBuilder;
+use crate::dictionary::DictionaryBuilder;
+use crate::guide::GuideBuilder;
+use crate::python::{CompletionDawg, PAYLOAD_SEPARATOR};
+use std::io::{Read, Write};
+
+pub struct BytesDictionary {
+    completion: CompletionDawg,
+    separator: u8,
+}
+
+impl BytesDictionary {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        BytesDictionary::from_reader_with_separator(reader, PAYLOAD_SEPARATOR)
+    }
+
+    /// Reads a file built with a custom `payload_separator`.
+    pub fn from_reader_with_separator<T: Read>(reader: &mut T, separator: u8) -> Option<Self> {
+        let completion = CompletionDawg::from_reader(reader)?;
+        Some(BytesDictionary {
+            completion,
+            separator,
+        })
+    }
+
+    /// Writes the file `BytesDAWG.load` reads; the separator is not part of it.
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.completion.write(writer)
+    }
+
+    pub fn completion(&self) -> &CompletionDawg {
+        &self.completion
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        let dictionary = self.completion.dictionary();
+        dictionary
+            .follow_bytes(key, dictionary.root())
+            .and_then(|index| dictionary.follow(self.separator, index))
+            .is_some()
+    }
+
+    /// All values stored for `key`, empty when there are none.
+    ///
+    /// Values come in the order of their base64 encodings, as from `BytesDAWG.get`, rather
+    /// than their own: `[0xFF]` ("/w==") comes before `[0x00]` ("AA==").
+    pub fn get(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        let mut prefix = key.to_vec();
+        prefix.push(self.separator);
+        self.completion
+            .complete(&prefix)
+            .filter_map(|(encoded, _)| base64::decode(&encoded[prefix.len()..]))
+            .collect()
+    }
+
+    /// Pairs of keys starting with `prefix` and their values, by key and then in the order of
+    /// `get`.
+    pub fn items<'a>(&'a self, prefix: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a {
+        let separator = self.separator;
+        self.completion
+            .complete(prefix)
+            .filter_map(move |(mut encoded, _)| {
+                let position = encoded.iter().position(|&byte| byte == separator)?;
+                let value = base64::decode(&encoded[position + 1..])?;
+                encoded.truncate(position);
+                Some((encoded, value))
+            })
+    }
+}
+
+/// Collects key and value pairs in any order, then builds a `BytesDictionary` of them.
+pub struct BytesDictionaryBuilder {
+    separator: u8,
+    keys: Vec<Vec<u8>>,
+}
+
+impl Default for BytesDictionaryBuilder {
+    fn default() -> Self {
+        BytesDictionaryBuilder::new()
+    }
+}
+
+impl BytesDictionaryBuilder {
+    pub fn new() -> BytesDictionaryBuilder {
+        BytesDictionaryBuilder::with_separator(PAYLOAD_SEPARATOR)
+    }
+
+    /// # Panics
+    ///
+    /// If `separator` is 0, which terminates keys.
+    pub fn with_separator(separator: u8) -> BytesDictionaryBuilder {
+        assert_ne!(separator, 0, "separator must not be 0");
+        BytesDictionaryBuilder {
+            separator,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Adds `value` to the values of `key`, failing if `key` contains 0 or the separator.
+    ///
+    /// Inserting the same pair twice stores it once.
+    #[allow(clippy::result_unit_err)]
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), ()> {
+        if key.iter().any(|&byte| byte == 0 || byte == self.separator) {
+            return Err(());
+        }
+        let mut encoded = key.to_vec();
+        encoded.push(self.separator);
+        encoded.extend_from_slice(&base64::encode(value));
+        self.keys.push(encoded);
+        Ok(())
+    }
+
+    pub fn build(mut self) -> BytesDictionary {
+        self.keys.sort_unstable();
+        self.keys.dedup();
+        let mut builder = DawgBuilder::new();
+        for mut key in self.keys {
+            key.push(0);
+            builder
+                .insert_key_bytes(&key, 0)
+                .expect("keys are sorted and unique");
+        }
+        let dictionary = DictionaryBuilder::new(builder.build()).build();
+        let guide = GuideBuilder::new(&dictionary).build();
+        BytesDictionary {
+            completion: CompletionDawg::new(dictionary, guide),
+            separator: self.separator,
+        }
+    }
+}
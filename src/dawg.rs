@@ -1,10 +1,14 @@
-use crate::pool::Pool;
+use crate::dot;
+use crate::pool::{BitPool, Pool};
 use crate::unit::BaseType;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::convert::TryFrom;
+use std::io::{Read, Write};
 
 pub struct Dawg {
     base_pool: Pool<BaseUnit>,
     label_pool: Pool<u8>,
-    flag_pool: Pool<bool>, // TODO: BitPool tool
+    flag_pool: BitPool,
     num_states: BaseType,
     num_merged_transitions: BaseType,
     num_merged_states: BaseType,
@@ -12,6 +16,65 @@ pub struct Dawg {
 }
 
 impl Dawg {
+    /// Reads a DAWG written by `write`.
+    ///
+    /// Transitions are checked to form an acyclic graph with in-bounds, label-ordered children,
+    /// and states entered by more than one transition are checked to be flagged as merging, so
+    /// that anything read can be fed to `DictionaryBuilder` without laying a state out twice.
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        let size = reader.read_u32::<LittleEndian>().ok()?;
+        let num_states = reader.read_u32::<LittleEndian>().ok()?;
+        let num_merged_transitions = reader.read_u32::<LittleEndian>().ok()?;
+        let num_merged_states = reader.read_u32::<LittleEndian>().ok()?;
+        let num_merging_states = reader.read_u32::<LittleEndian>().ok()?;
+
+        let base_pool = Pool::read_from(reader, size, |reader| {
+            reader.read_u32::<LittleEndian>().map(BaseUnit)
+        })?;
+        let label_pool = read_bytes(reader, usize::try_from(size).ok()?)?;
+        let flag_bytes = read_bytes(reader, usize::try_from(size.div_ceil(8)).ok()?)?;
+        let flag_pool = BitPool::from_bytes(&flag_bytes, size)?;
+
+        let dawg = Dawg {
+            base_pool,
+            label_pool: Pool::from_vec(label_pool),
+            flag_pool,
+            num_states,
+            num_merged_transitions,
+            num_merged_states,
+            num_merging_states,
+        };
+        Some(dawg).filter(Dawg::is_well_formed)
+    }
+
+    /// Writes the transitions and counters of the DAWG.
+    ///
+    /// The format is specific to this library; `Dawg::from_reader` reads it back.
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        let size = self.base_pool.len();
+        writer.write_u32::<LittleEndian>(size)?;
+        writer.write_u32::<LittleEndian>(self.num_states)?;
+        writer.write_u32::<LittleEndian>(self.num_merged_transitions)?;
+        writer.write_u32::<LittleEndian>(self.num_merged_states)?;
+        writer.write_u32::<LittleEndian>(self.num_merging_states)?;
+        for unit in self.base_pool.iter() {
+            writer.write_u32::<LittleEndian>(unit.base())?;
+        }
+        let labels: Vec<u8> = self.label_pool.iter().copied().collect();
+        writer.write_all(&labels)?;
+        writer.write_all(&self.flag_pool.to_bytes())
+    }
+
+    /// Bytes of heap memory held by the DAWG.
+    pub fn heap_size(&self) -> usize {
+        self.base_pool.heap_size() + self.label_pool.heap_size() + self.flag_pool.heap_size()
+    }
+
+    /// Number of bytes `write` produces.
+    pub fn serialized_size(&self) -> usize {
+        serialized_size(self.base_pool.len())
+    }
+
     pub fn child(&self, index: BaseType) -> BaseType {
         self.base_pool[index].child()
     }
@@ -37,7 +100,16 @@ impl Dawg {
     }
 
     pub fn is_merging(&self, index: BaseType) -> bool {
-        self.flag_pool[index]
+        self.flag_pool.get(index).unwrap_or(false)
+    }
+
+    // Dense id of a merging state among all merging states, in transition order
+    pub(crate) fn merging_id(&self, index: BaseType) -> BaseType {
+        self.flag_pool.rank(index)
+    }
+
+    pub(crate) fn merging_id_count(&self) -> BaseType {
+        self.flag_pool.count_ones()
     }
 
     pub fn states_count(&self) -> BaseType {
@@ -60,11 +132,97 @@ impl Dawg {
         self.num_merging_states
     }
 
+    /// Writes the DAWG as a Graphviz digraph, for debugging.
+    ///
+    /// States are named after their first transition, and states flagged by `is_merging`
+    /// are highlighted. Leaf transitions are drawn as boxes holding their values.
+    pub fn to_dot<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        let root = self.child(0);
+        dot::write_header(writer, root)?;
+        let mut is_visited = vec![false; usize::try_from(self.base_pool.len()).unwrap()];
+        is_visited[usize::try_from(root).unwrap()] = true;
+        let mut states = vec![root];
+        while let Some(state) = states.pop() {
+            dot::write_state(writer, state, state != root && self.is_merging(state))?;
+            // Only the root of an empty DAWG has no transitions
+            let mut index = state;
+            while index != 0 {
+                if self.is_leaf(index) {
+                    dot::write_value(writer, index, self.value(index))?;
+                    dot::write_value_link(writer, state, index)?;
+                } else {
+                    let child = self.child(index);
+                    dot::write_transition(writer, state, child, self.label(index))?;
+                    let is_child_visited = &mut is_visited[usize::try_from(child).unwrap()];
+                    if !*is_child_visited {
+                        *is_child_visited = true;
+                        states.push(child);
+                    }
+                }
+                index = self.sibling(index);
+            }
+        }
+        dot::write_footer(writer)
+    }
+
     pub fn print(&self) {
         for item in self.base_pool.iter() {
             println!("{}", item.base())
         }
     }
+
+    // Children of a transition always precede it, except for those of the root, and
+    // siblings are ordered by label with the leaf first, as `DawgBuilder` lays them out
+    fn is_well_formed(&self) -> bool {
+        let size = self.base_pool.len();
+        if size == 0 {
+            return false;
+        }
+        let is_ordered = (0..size).all(|index| {
+            let unit = self.base_pool[index];
+            let label = self.label_pool[index];
+            if unit.has_sibling() && (index + 1 >= size || self.label_pool[index + 1] <= label) {
+                return false;
+            }
+            if index != 0 && label == 0 {
+                return true;
+            }
+            let child = unit.child();
+            if index == 0 && size == 1 {
+                return child == 0;
+            }
+            child != 0 && child < size && (index == 0 || child < index)
+        });
+        is_ordered && self.are_shared_states_merging()
+    }
+
+    // `DictionaryBuilder` lays out a state once per transition entering it unless it is flagged
+    // as merging, which takes exponential time on a DAWG sharing unflagged states. So states
+    // must start right after the last transition of another one, and states entered more
+    // than once must be flagged.
+    fn are_shared_states_merging(&self) -> bool {
+        let size = self.base_pool.len();
+        let mut is_entered = vec![false; usize::try_from(size).unwrap()];
+        (0..size).all(|index| {
+            if index != 0 && self.label_pool[index] == 0 {
+                return true;
+            }
+            let child = self.base_pool[index].child();
+            if child == 0 {
+                // Only the root of an empty DAWG has no child
+                return true;
+            }
+            if self.base_pool[child - 1].has_sibling() {
+                return false;
+            }
+            let is_child_entered = &mut is_entered[usize::try_from(child).unwrap()];
+            if *is_child_entered && !self.is_merging(child) {
+                return false;
+            }
+            *is_child_entered = true;
+            true
+        })
+    }
 }
 
 #[repr(transparent)]
@@ -97,13 +255,29 @@ impl BaseUnit {
     }
 }
 
+// Bytes `Dawg::write` takes for `size` transitions, the root included
+fn serialized_size(size: BaseType) -> usize {
+    let size = usize::try_from(size).unwrap();
+    20 + size * (std::mem::size_of::<BaseType>() + 1) + size.div_ceil(8)
+}
+
+// Reads exactly `len` bytes, allocating only for those that arrive
+fn read_bytes<T: Read>(reader: &mut T, len: usize) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(u64::try_from(len).ok()?)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    Some(bytes).filter(|bytes| bytes.len() == len)
+}
+
 // BUILDER
 
 #[derive(Default)]
 pub struct DawgBuilder {
     base_pool: Pool<BaseUnit>,
     label_pool: Pool<u8>,
-    flag_pool: Pool<bool>,
+    flag_pool: BitPool,
     unit_pool: Pool<DawgUnit>,
     hash_table: Pool<BaseType>,
     unfixed_units: Vec<BaseType>,
@@ -115,14 +289,16 @@ pub struct DawgBuilder {
 
 impl DawgBuilder {
     pub fn new() -> DawgBuilder {
-        let mut builder: DawgBuilder = Default::default();
-        builder.hash_table = {
-            let mut hash_table = Vec::new();
-            let initial_size = 1 << 8;
-            hash_table.resize(initial_size, 0);
-            Pool::from_vec(hash_table)
+        let mut builder = DawgBuilder {
+            hash_table: {
+                let mut hash_table = Vec::new();
+                let initial_size = 1 << 8;
+                hash_table.resize(initial_size, 0);
+                Pool::from_vec(hash_table)
+            },
+            num_states: 1,
+            ..Default::default()
         };
-        builder.num_states = 1;
         builder.reuse_or_create_unit();
         builder.allocate_transition();
         builder.unit_pool[0].set_label(0xFF);
@@ -130,13 +306,15 @@ impl DawgBuilder {
         builder
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn insert_key(&mut self, key: &str, value: BaseType) -> Result<(), ()> {
         let mut bytes: Vec<u8> = key.bytes().collect();
         bytes.push(0);
         self.insert_key_bytes(&bytes, value)
     }
 
-    fn insert_key_bytes(&mut self, key: &[u8], value: BaseType) -> Result<(), ()> {
+    // Inserts `key` including its terminating 0
+    pub(crate) fn insert_key_bytes(&mut self, key: &[u8], value: BaseType) -> Result<(), ()> {
         let mut index: BaseType = 0;
         let mut key_pos: usize = 0;
 
@@ -184,6 +362,39 @@ impl DawgBuilder {
         Ok(())
     }
 
+    /// Bytes of heap memory held by the builder so far, lookup tables included.
+    pub fn heap_size(&self) -> usize {
+        self.base_pool.heap_size()
+            + self.label_pool.heap_size()
+            + self.flag_pool.heap_size()
+            + self.unit_pool.heap_size()
+            + self.hash_table.heap_size()
+            + (self.unfixed_units.capacity() + self.unused_units.capacity())
+                * std::mem::size_of::<BaseType>()
+    }
+
+    /// Upper bound on the `Dawg::serialized_size` of what `build` would return now.
+    ///
+    /// Transitions along the last key inserted are only merged by `build`, so they are counted
+    /// as if none of them merged.
+    pub fn serialized_size(&self) -> usize {
+        let pending_count: BaseType = self
+            .unfixed_units
+            .iter()
+            .skip(1)
+            .map(|&index| {
+                let mut count = 0;
+                let mut i = index;
+                while i != 0 {
+                    count += 1;
+                    i = self.unit_pool[i].sibling;
+                }
+                count
+            })
+            .sum();
+        serialized_size(self.base_pool.len() + pending_count)
+    }
+
     pub fn build(mut self) -> Dawg {
         self.fix_units(0);
         self.base_pool[0].set_base(self.unit_pool[0].base());
@@ -235,9 +446,9 @@ impl DawgBuilder {
                 // TODO: avoid mutating lots of disparate fields
                 self.num_merged_transitions += num_of_siblings;
 
-                if !self.flag_pool[matched_index] {
+                if self.flag_pool.get(matched_index) == Some(false) {
                     self.num_merging_states += 1;
-                    self.flag_pool[matched_index] = true;
+                    self.flag_pool.set(matched_index, true);
                 }
             } else {
                 let mut transition_index = 0;
@@ -261,12 +472,11 @@ impl DawgBuilder {
 
             // Marking all fixed units for reuse
             let mut current = unfixed_index;
-            let mut next = 0;
             loop {
                 if current == 0 {
                     break;
                 }
-                next = self.unit_pool[current].sibling;
+                let next = self.unit_pool[current].sibling;
                 self.mark_unit_as_unused(current);
                 current = next;
             }
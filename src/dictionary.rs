@@ -1,43 +1,306 @@
+use crate::automaton::{Automaton, KeyRange, Search};
+#[cfg(feature = "compression")]
+use crate::compression;
 use crate::dawg::Dawg;
+use crate::dot;
+use crate::header::{self, ChecksumReader, Header};
 use crate::pool::Pool;
 use crate::unit::BaseType;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::io::{Read, Write};
+use std::ops::RangeBounds;
+use std::sync::OnceLock;
 
 // Dictionary
 
-pub struct Dictionary {
+pub struct Dictionary<U = Pool<DictionaryUnit>> {
     root: u32,
-    units: Pool<DictionaryUnit>,
+    units: U,
+    key_counts: OnceLock<Pool<BaseType>>,
 }
 
+/// Dictionary reading its units in place from a borrowed buffer.
+pub type DictionaryRef<'a> = Dictionary<UnitSlice<'a>>;
+
+/// Dictionary of 64-bit units, for when offsets outgrow the 32-bit layout.
+///
+/// Unit indices remain `BaseType`, which caps it just below 2^32 units rather than at 2^29.
+pub type LargeDictionary = Dictionary<Pool<LargeDictionaryUnit>>;
+
 impl Dictionary {
+    /// Reads a dictionary written by `write`, `write_with_header` or `write_compressed`.
+    ///
+    /// When a header is present the payload must match its checksum. Files of 64-bit units
+    /// are rejected, see `LargeDictionary::from_large_reader` and `AutoDictionary::from_reader`.
     pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic).ok()?;
+        if magic != header::MAGIC {
+            return Dictionary::read_units(u32::from_le_bytes(magic), reader);
+        }
+        Dictionary::read_after_header(Header::read_after_magic(reader)?, reader)
+    }
+
+    /// Same as `from_reader`, but also rejects dictionaries failing `validate`.
+    pub fn from_reader_checked<T: Read>(reader: &mut T) -> Option<Self> {
+        Dictionary::from_reader(reader).filter(|dictionary| dictionary.validate().is_ok())
+    }
+}
+
+impl LargeDictionary {
+    /// Reads a dictionary of 64-bit units, which always comes with a header.
+    pub fn from_large_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic).ok()?;
+        if magic != header::MAGIC {
+            return None;
+        }
+        LargeDictionary::read_after_header(Header::read_after_magic(reader)?, reader)
+    }
+}
+
+impl<V: Unit> Dictionary<Pool<V>> {
+    // Reads the payload described by `header`, if it holds units of this layout
+    fn read_after_header<T: Read>(header: Header, reader: &mut T) -> Option<Self> {
+        if header.flags & header::FLAG_LARGE_UNITS != V::HEADER_FLAGS {
+            return None;
+        }
+        #[cfg(feature = "compression")]
+        if header.flags & header::FLAG_COMPRESSED != 0 {
+            let dictionary = Dictionary::from_units(compression::read_units(reader)?);
+            return Some(dictionary).filter(|dictionary| dictionary.checksum() == header.checksum);
+        }
+        let mut reader = ChecksumReader::new(reader);
         let size = reader.read_u32::<LittleEndian>().ok()?;
-        let size = usize::try_from(size).ok()?;
-        let mut units = Vec::with_capacity(size);
-        for _ in 0..size {
-            let unit = reader.read_u32::<LittleEndian>().ok()?;
-            units.push(DictionaryUnit(unit))
+        let dictionary = Dictionary::read_units(size, &mut reader)?;
+        if reader.checksum() != header.checksum {
+            return None;
+        }
+        Some(dictionary)
+    }
+
+    fn read_units<T: Read>(size: u32, reader: &mut T) -> Option<Self> {
+        let units = Pool::read_from(reader, size, |reader| {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes[..V::SIZE])?;
+            Ok(V::from_bits(u64::from_le_bytes(bytes)))
+        })?;
+        Some(Dictionary::from_units(units))
+    }
+}
+
+// Bytes `Dictionary::write` takes for `size` units of type `V`
+fn serialized_size<V: Unit>(size: BaseType) -> usize {
+    let header_size = if V::HEADER_FLAGS != 0 {
+        Header::SIZE
+    } else {
+        0
+    };
+    header_size + 4 + usize::try_from(size).unwrap() * V::SIZE
+}
+
+/// Dictionary in whichever unit layout `DictionaryBuilder::build_auto` settled on.
+pub enum AutoDictionary<V: Unit = DictionaryUnit> {
+    Compact(Dictionary<Pool<V>>),
+    Large(LargeDictionary),
+}
+
+impl AutoDictionary {
+    /// Reads a dictionary of either layout, telling them apart by the header.
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic).ok()?;
+        if magic != header::MAGIC {
+            return Dictionary::read_units(u32::from_le_bytes(magic), reader)
+                .map(AutoDictionary::Compact);
+        }
+        let header = Header::read_after_magic(reader)?;
+        if header.flags & header::FLAG_LARGE_UNITS != 0 {
+            LargeDictionary::read_after_header(header, reader).map(AutoDictionary::Large)
+        } else {
+            Dictionary::read_after_header(header, reader).map(AutoDictionary::Compact)
+        }
+    }
+}
+
+impl<V: Unit> AutoDictionary<V> {
+    /// Writes the dictionary with a header, which is what tells the layouts apart.
+    pub fn write_with_header<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        match self {
+            AutoDictionary::Compact(dictionary) => dictionary.write_with_header(writer),
+            AutoDictionary::Large(dictionary) => dictionary.write_with_header(writer),
+        }
+    }
+
+    pub fn is_large(&self) -> bool {
+        matches!(self, AutoDictionary::Large(_))
+    }
+
+    pub fn size(&self) -> BaseType {
+        match self {
+            AutoDictionary::Compact(dictionary) => dictionary.size(),
+            AutoDictionary::Large(dictionary) => dictionary.size(),
+        }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        match self {
+            AutoDictionary::Compact(dictionary) => dictionary.contains(key),
+            AutoDictionary::Large(dictionary) => dictionary.contains(key),
         }
-        let units = Pool::from_vec(units);
-        Some(Dictionary { root: 0, units })
     }
 
-    pub fn write<T: Write>(&self, writer: &mut T) -> Result<(), ()> {
+    pub fn find(&self, key: &[u8]) -> Option<u32> {
+        match self {
+            AutoDictionary::Compact(dictionary) => dictionary.find(key),
+            AutoDictionary::Large(dictionary) => dictionary.find(key),
+        }
+    }
+
+    pub fn key_count(&self) -> BaseType {
+        match self {
+            AutoDictionary::Compact(dictionary) => dictionary.key_count(),
+            AutoDictionary::Large(dictionary) => dictionary.key_count(),
+        }
+    }
+}
+
+impl<'a> Dictionary<UnitSlice<'a>> {
+    /// Borrows a dictionary from bytes laid out as by `write` or `write_with_header`,
+    /// e.g. a memory-mapped file. Compressed files cannot be borrowed.
+    ///
+    /// Nothing is copied: units are decoded on access, so `bytes` need no particular alignment.
+    /// A header's checksum is still verified, which touches every byte once.
+    pub fn from_bytes(bytes: &'a [u8]) -> Option<Self> {
+        let payload = if bytes.starts_with(&header::MAGIC) {
+            let header = Header::from_bytes(bytes)?;
+            if header.flags & (header::FLAG_COMPRESSED | header::FLAG_LARGE_UNITS) != 0 {
+                return None;
+            }
+            let payload = Self::unit_section(&bytes[Header::SIZE..])?;
+            if header::checksum(payload) != header.checksum {
+                return None;
+            }
+            payload
+        } else {
+            Self::unit_section(bytes)?
+        };
+        Some(Dictionary::from_units(UnitSlice {
+            bytes: &payload[4..],
+        }))
+    }
+
+    /// Same as `from_bytes`, but also rejects dictionaries failing `validate`.
+    pub fn from_bytes_checked(bytes: &'a [u8]) -> Option<Self> {
+        Dictionary::from_bytes(bytes).filter(|dictionary| dictionary.validate().is_ok())
+    }
+
+    // Unit count followed by exactly that many units
+    fn unit_section(bytes: &'a [u8]) -> Option<&'a [u8]> {
+        let size = bytes.get(0..4)?;
+        let size = usize::try_from(u32::from_le_bytes(size.try_into().ok()?)).ok()?;
+        bytes.get(..size.checked_mul(4)?.checked_add(4)?)
+    }
+}
+
+impl<U: UnitStorage> Dictionary<U> {
+    pub(crate) fn from_units(units: U) -> Dictionary<U> {
+        Dictionary {
+            root: 0,
+            units,
+            key_counts: OnceLock::new(),
+        }
+    }
+
+    /// Writes the unit count followed by the units, the format of the C++ dawgdic.
+    ///
+    /// That format has no room for 64-bit units, which are written with a header instead,
+    /// so that readers of 32-bit units reject them.
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        if U::Unit::HEADER_FLAGS != 0 {
+            return self.write_with_header(writer);
+        }
+        self.write_units(writer)
+    }
+
+    /// Writes the same units as `write`, preceded by a `Header` describing and checksumming them.
+    pub fn write_with_header<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.header().write(writer)?;
+        self.write_units(writer)
+    }
+
+    fn write_units<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
         let size = self.units.len();
-        writer.write_u32::<LittleEndian>(size).expect("FIXME");
-        for unit in self.units.iter() {
-            writer.write_u32::<LittleEndian>(unit.0).expect("FIXME");
+        writer.write_u32::<LittleEndian>(size)?;
+        for index in 0..size {
+            let unit = self.units.get(index).unwrap_or_default();
+            writer.write_all(&unit.to_bits().to_le_bytes()[..U::Unit::SIZE])?;
         }
         Ok(())
     }
 
+    /// Writes a `Header` followed by the units deflated, typically about 60% of the size of `write`.
+    #[cfg(feature = "compression")]
+    pub fn write_compressed<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        let header = self.header();
+        let header = Header {
+            flags: header.flags | header::FLAG_COMPRESSED,
+            ..header
+        };
+        header.write(writer)?;
+        compression::write_units(&self.units, writer)
+    }
+
+    pub(crate) fn header(&self) -> Header {
+        Header {
+            version: header::VERSION,
+            flags: U::Unit::HEADER_FLAGS,
+            key_count: self.header_key_count(),
+            checksum: self.checksum(),
+        }
+    }
+
+    // Checksum of the units as written after a header
+    pub(crate) fn checksum(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&self.units.len().to_le_bytes());
+        for index in 0..self.units.len() {
+            let unit = self.units.get(index).unwrap_or_default();
+            hasher.update(&unit.to_bits().to_le_bytes()[..U::Unit::SIZE]);
+        }
+        hasher.finalize()
+    }
+
+    pub fn root(&self) -> BaseType {
+        self.root
+    }
+
     pub fn size(&self) -> BaseType {
         self.units.len()
     }
 
+    // Whether a traversal may add a label to a key of `key_len` bytes. Keys of a well-formed
+    // dictionary visit every state at most once, so anything longer runs through a cycle
+    pub(crate) fn can_extend_key(&self, key_len: usize) -> bool {
+        key_len < usize::try_from(self.size()).unwrap_or(usize::MAX)
+    }
+
+    /// Bytes of heap memory held by the dictionary, including key counts cached for ranking.
+    pub fn heap_size(&self) -> usize {
+        self.units.heap_size() + self.key_counts.get().map_or(0, Pool::heap_size)
+    }
+
+    /// Number of bytes `write` produces.
+    pub fn serialized_size(&self) -> usize {
+        serialized_size::<U::Unit>(self.size())
+    }
+
+    pub fn units(&self) -> &U {
+        &self.units
+    }
+
     pub fn has_value(&self, index: u32) -> bool {
         self.units
             .get(index)
@@ -55,18 +318,19 @@ impl Dictionary {
 
     pub fn contains(&self, key: &[u8]) -> bool {
         self.follow_bytes(key, self.root)
-            .map_or(false, |index| self.has_value(index))
+            .is_some_and(|index| self.has_value(index))
     }
 
     pub fn find(&self, key: &[u8]) -> Option<u32> {
         self.follow_bytes(key, self.root)
+            .filter(|&index| self.has_value(index))
             .and_then(|index| self.value(index))
     }
 
     pub fn follow(&self, label: u8, index: u32) -> Option<u32> {
-        let unit = self.units[index];
+        let unit = self.units.get(index)?;
         let next_index = index ^ unit.offset() ^ u32::from(label);
-        let leaf_label = self.units[next_index].label();
+        let leaf_label = self.units.get(next_index)?.label();
         if leaf_label != u32::from(label) {
             return None;
         }
@@ -80,12 +344,501 @@ impl Dictionary {
         }
         Some(index)
     }
+
+    /// Checks the structure of a dictionary coming from an untrusted source.
+    ///
+    /// Every offset must point inside the unit array, every value flag must lead to a value,
+    /// and the states reachable from the root must form an acyclic graph whose key count fits
+    /// in `BaseType`. Lookups on a dictionary passing this check terminate and give consistent
+    /// results.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let size = self.units.len();
+        if self.units.get(self.root).is_none() {
+            return Err(ValidationError::MissingRoot);
+        }
+
+        for index in 0..size {
+            let unit = self.units.get(index).unwrap_or_default();
+            if unit.is_leaf() {
+                continue;
+            }
+            let block = index ^ unit.offset();
+            if block | 0xFF >= size {
+                return Err(ValidationError::OffsetOutOfBounds(index));
+            }
+            if unit.has_leaf() && !self.units.get(block).is_some_and(|unit| unit.is_leaf()) {
+                return Err(ValidationError::MissingValue(index));
+            }
+        }
+
+        let key_counts = self.count_keys(true)?;
+        let _ = self.key_counts.set(key_counts);
+        Ok(())
+    }
+
+    /// Streams the keys accepted by `automaton` along with their values, in lexicographic order.
+    pub fn search<A: Automaton>(&self, automaton: A) -> Search<'_, A, U> {
+        Search::new(self, automaton)
+    }
+
+    /// Streams the keys within `range` along with their values, in lexicographic order.
+    pub fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> Search<'_, KeyRange, U> {
+        self.search(KeyRange::new(range))
+    }
+
+    /// Returns the position of `key` among all keys in lexicographic order.
+    ///
+    /// Ordinals are dense, so this is a minimal perfect hash for the key set.
+    pub fn index_of(&self, key: &[u8]) -> Option<BaseType> {
+        let key_counts = self.key_counts();
+        let mut index = self.root;
+        let mut ordinal: BaseType = 0;
+        for &label in key {
+            if self.has_value(index) {
+                ordinal = ordinal.saturating_add(1);
+            }
+            for (_, child_index) in self.children(index).take_while(|&(l, _)| l < label) {
+                ordinal = key_counts[child_index].saturating_add(ordinal);
+            }
+            index = self.follow(label, index)?;
+        }
+        if !self.has_value(index) {
+            return None;
+        }
+        Some(ordinal)
+    }
+
+    /// Reconstructs the key at position `ordinal` in lexicographic order; the inverse of `index_of`.
+    pub fn key_at(&self, ordinal: BaseType) -> Option<Vec<u8>> {
+        let key_counts = self.key_counts();
+        let mut ordinal = ordinal;
+        let mut index = self.root;
+        let mut key = Vec::new();
+        'descend: loop {
+            if !self.can_extend_key(key.len()) {
+                return None;
+            }
+            if self.has_value(index) {
+                if ordinal == 0 {
+                    return Some(key);
+                }
+                ordinal -= 1;
+            }
+            for (label, child_index) in self.children(index) {
+                let count = key_counts[child_index];
+                if ordinal < count {
+                    key.push(label);
+                    index = child_index;
+                    continue 'descend;
+                }
+                ordinal -= count;
+            }
+            return None;
+        }
+    }
+
+    pub fn key_count(&self) -> BaseType {
+        self.key_counts().get(self.root).copied().unwrap_or(0)
+    }
+
+    /// Counts the keys starting with `prefix` without enumerating them.
+    pub fn count_prefix(&self, prefix: &[u8]) -> u64 {
+        self.follow_bytes(prefix, self.root)
+            .and_then(|index| self.key_counts().get(index))
+            .map_or(0, |&count| u64::from(count))
+    }
+
+    /// Measures the layout of the units, e.g. to track dictionary size across builds.
+    ///
+    /// Everything is derived from the units, so dictionaries read from files are covered too.
+    pub fn stats(&self) -> DictionaryStats {
+        let size = usize::try_from(self.size()).unwrap_or(usize::MAX);
+        let mut is_used = vec![false; size];
+        let mut extension_offsets = 0;
+        if let Some(is_root_used) = is_used.get_mut(usize::try_from(self.root).unwrap_or(size)) {
+            *is_root_used = true;
+        }
+        let mut unvisited = vec![self.root];
+        while let Some(index) = unvisited.pop() {
+            let Some(unit) = self.units.get(index) else {
+                continue;
+            };
+            if unit.has_extension() {
+                extension_offsets += 1;
+            }
+            if unit.has_leaf() {
+                let leaf = usize::try_from(index ^ unit.offset()).unwrap_or(size);
+                if let Some(is_leaf_used) = is_used.get_mut(leaf) {
+                    *is_leaf_used = true;
+                }
+            }
+            for (_, child) in self.children(index) {
+                let is_child_used = &mut is_used[usize::try_from(child).unwrap()];
+                if !*is_child_used {
+                    *is_child_used = true;
+                    unvisited.push(child);
+                }
+            }
+        }
+
+        let used_units = is_used.iter().filter(|&&is_used| is_used).count();
+        let used_units = BaseType::try_from(used_units).unwrap();
+        DictionaryStats {
+            total_units: self.size(),
+            used_units,
+            unused_units: self.size() - used_units,
+            fill_ratio: if self.size() == 0 {
+                0.0
+            } else {
+                f64::from(used_units) / f64::from(self.size())
+            },
+            extension_offsets,
+            key_count: self.key_count(),
+            max_key_length: self.max_key_length(),
+            memory_footprint: size * U::Unit::SIZE,
+        }
+    }
+
+    /// Writes the states reachable from the root as a Graphviz digraph, for debugging.
+    ///
+    /// States are named after their unit index, and those shared by several parents are
+    /// highlighted. Values are drawn as boxes named after their leaf units.
+    pub fn to_dot<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        let mut transitions = Vec::new();
+        let mut incoming: HashMap<BaseType, u32> = HashMap::new();
+        let mut states = vec![self.root];
+        let mut unvisited = vec![self.root];
+        incoming.insert(self.root, 0);
+        while let Some(index) = unvisited.pop() {
+            for (label, child) in self.children(index) {
+                transitions.push((index, child, label));
+                let count = incoming.entry(child).or_insert(0);
+                if *count == 0 && child != self.root {
+                    states.push(child);
+                    unvisited.push(child);
+                }
+                *count += 1;
+            }
+        }
+
+        dot::write_header(writer, self.root)?;
+        let mut leaves = HashSet::new();
+        for &state in &states {
+            dot::write_state(writer, state, incoming[&state] > 1)?;
+            if self.has_value(state) {
+                let leaf = state ^ self.units.get(state).map_or(0, |unit| unit.offset());
+                if leaves.insert(leaf) {
+                    dot::write_value(writer, leaf, self.value(state).unwrap_or(0))?;
+                }
+                dot::write_value_link(writer, state, leaf)?;
+            }
+        }
+        for (from, to, label) in transitions {
+            dot::write_transition(writer, from, to, label)?;
+        }
+        dot::write_footer(writer)
+    }
+
+    fn children(&self, index: BaseType) -> impl Iterator<Item = (u8, BaseType)> + '_ {
+        (1..=0xFF).filter_map(move |label| Some((label, self.follow(label, index)?)))
+    }
+
+    // Post-order walk computing the longest key below every state; states closing a cycle
+    // count as having no keys, so that malformed dictionaries still get a number
+    fn max_key_length(&self) -> usize {
+        let size = usize::try_from(self.size()).unwrap_or(usize::MAX);
+        if self.units.get(self.root).is_none() {
+            return 0;
+        }
+        let mut lengths: Vec<Option<usize>> = vec![None; size];
+        let mut is_done = vec![false; size];
+        let mut is_open = vec![false; size];
+        is_open[usize::try_from(self.root).unwrap()] = true;
+        let mut stack = vec![(self.root, self.children(self.root).collect::<Vec<_>>(), 0)];
+        while let Some((index, children, position)) = stack.last_mut() {
+            if let Some(&(_, child)) = children.get(*position) {
+                *position += 1;
+                let child_position = usize::try_from(child).unwrap();
+                if !is_done[child_position] && !is_open[child_position] {
+                    is_open[child_position] = true;
+                    let grandchildren = self.children(child).collect();
+                    stack.push((child, grandchildren, 0));
+                }
+                continue;
+            }
+
+            let index = *index;
+            let position = usize::try_from(index).unwrap();
+            let leaf_length = Some(0).filter(|_| self.has_value(index));
+            lengths[position] = children
+                .iter()
+                .filter_map(|&(_, child)| lengths[usize::try_from(child).unwrap()])
+                .map(|length| length + 1)
+                .chain(leaf_length)
+                .max();
+            is_done[position] = true;
+            is_open[position] = false;
+            stack.pop();
+        }
+        lengths[usize::try_from(self.root).unwrap()].unwrap_or(0)
+    }
+
+    // Number of keys reachable from every state, computed on first use
+    fn key_counts(&self) -> &Pool<BaseType> {
+        self.key_counts
+            .get_or_init(|| self.count_keys(false).unwrap_or_default())
+    }
+
+    // Number of keys for a header, without caching the counts of every state just for it
+    fn header_key_count(&self) -> BaseType {
+        match self.key_counts.get() {
+            Some(key_counts) => key_counts.get(self.root).copied().unwrap_or(0),
+            None => self.walk_key_counts(false, |_, _| ()).unwrap_or(0),
+        }
+    }
+
+    fn count_keys(&self, strict: bool) -> Result<Pool<BaseType>, ValidationError> {
+        let mut key_counts = Pool::default();
+        key_counts.resize(self.units.len(), 0);
+        self.walk_key_counts(strict, |index, key_count| key_counts[index] = key_count)?;
+        Ok(key_counts)
+    }
+
+    // Depth-first walk over the states reachable from the root, passing the number of keys
+    // below each to `record` and returning that of the root.
+    //
+    // States sharing a block of children share their counts, so each block is expanded once.
+    // When `strict`, cycles and overflowing counts are errors; otherwise cycles contribute
+    // nothing and counts saturate, so that malformed dictionaries still get usable numbers.
+    fn walk_key_counts(
+        &self,
+        strict: bool,
+        mut record: impl FnMut(BaseType, BaseType),
+    ) -> Result<BaseType, ValidationError> {
+        enum Visit {
+            InProgress,
+            Done(BaseType),
+        }
+        struct Frame {
+            index: BaseType,
+            block: BaseType,
+            next_label: u16,
+            key_count: BaseType,
+        }
+
+        if self.units.get(self.root).is_none() {
+            return Ok(0);
+        }
+
+        let add = |sum: BaseType, count: BaseType| match sum.checked_add(count) {
+            Some(sum) => Ok(sum),
+            None if strict => Err(ValidationError::TooManyKeys),
+            None => Ok(BaseType::MAX),
+        };
+        let frame = |index: BaseType| Frame {
+            index,
+            block: index ^ self.units.get(index).map_or(0, |unit| unit.offset()),
+            next_label: 1,
+            key_count: BaseType::from(self.has_value(index)),
+        };
+
+        let mut root_key_count = 0;
+        let mut visits = HashMap::new();
+        let mut stack = vec![frame(self.root)];
+        visits.insert(stack[0].block, Visit::InProgress);
+
+        while let Some(top) = stack.last_mut() {
+            if top.next_label > 0xFF {
+                let Frame {
+                    index,
+                    block,
+                    key_count,
+                    ..
+                } = stack.pop().unwrap();
+                record(index, key_count);
+                visits.insert(block, Visit::Done(key_count));
+                match stack.last_mut() {
+                    Some(parent) => parent.key_count = add(parent.key_count, key_count)?,
+                    None => root_key_count = key_count,
+                }
+                continue;
+            }
+
+            let label = top.next_label as u8;
+            top.next_label += 1;
+            let child_index = match self.follow(label, top.index) {
+                Some(child_index) => child_index,
+                None => continue,
+            };
+
+            let child = frame(child_index);
+            match visits.get(&child.block) {
+                Some(Visit::InProgress) if strict => {
+                    return Err(ValidationError::Cycle(child_index));
+                }
+                Some(Visit::InProgress) => (),
+                Some(&Visit::Done(key_count)) => {
+                    record(child_index, key_count);
+                    top.key_count = add(top.key_count, key_count)?;
+                }
+                None => {
+                    visits.insert(child.block, Visit::InProgress);
+                    stack.push(child);
+                }
+            }
+        }
+
+        Ok(root_key_count)
+    }
 }
 
-// Unit type
+/// Layout metrics of a dictionary, as reported by `Dictionary::stats`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DictionaryStats {
+    pub total_units: BaseType,
+    /// Units reachable from the root, as states or as values.
+    pub used_units: BaseType,
+    /// Units left over by the layout, which only take up space.
+    pub unused_units: BaseType,
+    /// Share of `used_units` in `total_units`.
+    pub fill_ratio: f64,
+    /// Reachable states whose offset needed the extension bit.
+    pub extension_offsets: BaseType,
+    pub key_count: BaseType,
+    pub max_key_length: usize,
+    /// Bytes taken by the units.
+    pub memory_footprint: usize,
+}
 
-#[repr(transparent)]
+/// Reason a dictionary failed `Dictionary::validate`, carrying the offending unit index.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    MissingRoot,
+    OffsetOutOfBounds(BaseType),
+    MissingValue(BaseType),
+    Cycle(BaseType),
+    TooManyKeys,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::MissingRoot => write!(f, "dictionary has no root unit"),
+            ValidationError::OffsetOutOfBounds(index) => {
+                write!(f, "offset of unit {} points outside the dictionary", index)
+            }
+            ValidationError::MissingValue(index) => {
+                write!(
+                    f,
+                    "unit {} is flagged as having a value but has none",
+                    index
+                )
+            }
+            ValidationError::Cycle(index) => write!(f, "unit {} is part of a cycle", index),
+            ValidationError::TooManyKeys => write!(f, "key count overflows"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+// Unit storage
+
+/// Random access to the units of a dictionary, wherever they are kept.
+pub trait UnitStorage {
+    type Unit: Unit;
+
+    fn len(&self) -> BaseType;
+
+    fn get(&self, index: BaseType) -> Option<Self::Unit>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes of heap memory owned by the storage; borrowed storage owns none.
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<V: Unit> UnitStorage for Pool<V> {
+    type Unit = V;
+
+    fn len(&self) -> BaseType {
+        Pool::len(self)
+    }
+
+    fn get(&self, index: BaseType) -> Option<V> {
+        Pool::get(self, index).copied()
+    }
+
+    fn heap_size(&self) -> usize {
+        Pool::heap_size(self)
+    }
+}
+
+/// Units encoded as little-endian `u32`s in a borrowed buffer.
 #[derive(Copy, Clone)]
+pub struct UnitSlice<'a> {
+    bytes: &'a [u8],
+}
+
+impl UnitStorage for UnitSlice<'_> {
+    type Unit = DictionaryUnit;
+
+    fn len(&self) -> BaseType {
+        BaseType::try_from(self.bytes.len() / 4).unwrap()
+    }
+
+    fn get(&self, index: BaseType) -> Option<DictionaryUnit> {
+        let begin = usize::try_from(index).ok()?.checked_mul(4)?;
+        let bytes = self.bytes.get(begin..begin + 4)?;
+        Some(DictionaryUnit(u32::from_le_bytes(bytes.try_into().ok()?)))
+    }
+}
+
+// Unit types
+
+/// Layout of dictionary units, decoding the same fields from words of different widths.
+pub trait Unit: Copy + Default {
+    /// Bytes taken by a unit, in memory as well as in files.
+    const SIZE: usize;
+
+    /// `Header` flags marking files of units in this layout.
+    const HEADER_FLAGS: u32;
+
+    /// Whether `set_offset` can store `offset`; the builder only picks offsets that fit.
+    fn fits_offset(offset: BaseType) -> bool;
+
+    /// The unit zero-extended to 64 bits, whose low `SIZE` bytes are stored little-endian.
+    fn to_bits(&self) -> u64;
+
+    fn from_bits(bits: u64) -> Self;
+
+    fn is_leaf(&self) -> bool;
+
+    fn has_leaf(&self) -> bool;
+
+    fn set_has_leaf(&mut self);
+
+    fn value(&self) -> BaseType;
+
+    fn set_value(&mut self, value: BaseType);
+
+    fn label(&self) -> BaseType;
+
+    fn set_label(&mut self, value: BaseType);
+
+    fn has_extension(&self) -> bool;
+
+    fn offset(&self) -> BaseType;
+
+    fn set_offset(&mut self, offset: BaseType) -> bool;
+}
+
+#[repr(transparent)]
+#[derive(Copy, Clone, Default)]
 pub struct DictionaryUnit(pub u32);
 
 const PRECISION_MASK: u32 = 0xFFFF_FFFF;
@@ -95,6 +848,10 @@ const HAS_LEAF_BIT: u32 = 1 << 8;
 const EXTENSION_BIT: u32 = 1 << 9;
 
 impl DictionaryUnit {
+    pub fn is_leaf(&self) -> bool {
+        self.0 & IS_LEAF_BIT != 0
+    }
+
     pub fn has_leaf(&self) -> bool {
         self.0 & HAS_LEAF_BIT != 0
     }
@@ -119,6 +876,11 @@ impl DictionaryUnit {
         self.0 = (self.0 & !0xFF) | value
     }
 
+    /// Whether the offset is stored shifted by 8 bits, for offsets beyond `OFFSET_MAX`.
+    pub fn has_extension(&self) -> bool {
+        self.0 & EXTENSION_BIT != 0
+    }
+
     pub fn offset(&self) -> u32 {
         (self.0 >> 10) << ((self.0 & EXTENSION_BIT) >> 6)
     }
@@ -137,14 +899,152 @@ impl DictionaryUnit {
     }
 }
 
-pub struct DictionaryBuilder {
+impl Unit for DictionaryUnit {
+    const SIZE: usize = 4;
+
+    const HEADER_FLAGS: u32 = 0;
+
+    fn fits_offset(offset: BaseType) -> bool {
+        (offset & UPPER_MASK == 0) || (offset & LOWER_MASK == 0)
+    }
+
+    fn to_bits(&self) -> u64 {
+        u64::from(self.0)
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        DictionaryUnit(bits as u32)
+    }
+
+    fn is_leaf(&self) -> bool {
+        DictionaryUnit::is_leaf(self)
+    }
+
+    fn has_leaf(&self) -> bool {
+        DictionaryUnit::has_leaf(self)
+    }
+
+    fn set_has_leaf(&mut self) {
+        DictionaryUnit::set_has_leaf(self)
+    }
+
+    fn value(&self) -> BaseType {
+        DictionaryUnit::value(self)
+    }
+
+    fn set_value(&mut self, value: BaseType) {
+        DictionaryUnit::set_value(self, value)
+    }
+
+    fn label(&self) -> BaseType {
+        DictionaryUnit::label(self)
+    }
+
+    fn set_label(&mut self, value: BaseType) {
+        DictionaryUnit::set_label(self, value)
+    }
+
+    fn has_extension(&self) -> bool {
+        DictionaryUnit::has_extension(self)
+    }
+
+    fn offset(&self) -> BaseType {
+        DictionaryUnit::offset(self)
+    }
+
+    fn set_offset(&mut self, offset: BaseType) -> bool {
+        DictionaryUnit::set_offset(self, offset)
+    }
+}
+
+/// 64-bit unit storing any offset as is, for dictionaries whose offsets outgrow
+/// `DictionaryUnit`. Values still take 32 bits.
+///
+/// Bits 0-7 hold the label, bit 8 the leaf flag, bits 31-62 the offset. Leaves hold their
+/// value in bits 0-31 and set bit 63.
+#[repr(transparent)]
+#[derive(Copy, Clone, Default)]
+pub struct LargeDictionaryUnit(pub u64);
+
+const LARGE_IS_LEAF_BIT: u64 = 1 << 63;
+const LARGE_HAS_LEAF_BIT: u64 = 1 << 8;
+
+impl Unit for LargeDictionaryUnit {
+    const SIZE: usize = 8;
+
+    const HEADER_FLAGS: u32 = header::FLAG_LARGE_UNITS;
+
+    fn fits_offset(_offset: BaseType) -> bool {
+        true
+    }
+
+    fn to_bits(&self) -> u64 {
+        self.0
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        LargeDictionaryUnit(bits)
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.0 & LARGE_IS_LEAF_BIT != 0
+    }
+
+    fn has_leaf(&self) -> bool {
+        self.0 & LARGE_HAS_LEAF_BIT != 0
+    }
+
+    fn set_has_leaf(&mut self) {
+        self.0 |= LARGE_HAS_LEAF_BIT
+    }
+
+    fn value(&self) -> BaseType {
+        self.0 as BaseType
+    }
+
+    fn set_value(&mut self, value: BaseType) {
+        self.0 = u64::from(value) | LARGE_IS_LEAF_BIT
+    }
+
+    // Leaves report a label no transition has, like `DictionaryUnit::label`
+    fn label(&self) -> BaseType {
+        if self.is_leaf() {
+            IS_LEAF_BIT | (self.0 as BaseType & 0xFF)
+        } else {
+            self.0 as BaseType & 0xFF
+        }
+    }
+
+    fn set_label(&mut self, value: BaseType) {
+        self.0 = (self.0 & !0xFF) | u64::from(value)
+    }
+
+    fn has_extension(&self) -> bool {
+        false
+    }
+
+    fn offset(&self) -> BaseType {
+        ((self.0 & !LARGE_IS_LEAF_BIT) >> 31) as BaseType
+    }
+
+    fn set_offset(&mut self, offset: BaseType) -> bool {
+        self.0 =
+            (self.0 & (LARGE_IS_LEAF_BIT | LARGE_HAS_LEAF_BIT | 0xFF)) | (u64::from(offset) << 31);
+        true
+    }
+}
+
+pub struct DictionaryBuilder<V: Unit = DictionaryUnit> {
     dawg: Dawg,
-    units: Pool<DictionaryUnit>,
+    units: Pool<V>,
     extras: Pool<DictionaryExtra>,
     labels: Pool<u8>,
-    link_table: std::collections::HashMap<BaseType, BaseType>,
+    // Offsets of arranged merging states by `Dawg::merging_id`, 0 while not arranged yet
+    link_table: Vec<BaseType>,
     unfixed_index: BaseType,
     num_unused_nuts: BaseType,
+    // Outcome of `arrange`, once done
+    is_arranged: Option<bool>,
 }
 
 const UPPER_MASK: BaseType = !(OFFSET_MAX - 1);
@@ -152,6 +1052,18 @@ const LOWER_MASK: BaseType = 0xFF;
 
 impl DictionaryBuilder {
     pub fn new(dawg: Dawg) -> DictionaryBuilder {
+        DictionaryBuilder::with_layout(dawg)
+    }
+
+    /// Builds 64-bit units, whatever the size of the dictionary.
+    pub fn build_large(self) -> LargeDictionary {
+        DictionaryBuilder::<LargeDictionaryUnit>::with_layout(self.dawg).build()
+    }
+}
+
+impl<V: Unit> DictionaryBuilder<V> {
+    /// Builder of units of type `V`; `new` builds `DictionaryUnit`s.
+    pub fn with_layout(dawg: Dawg) -> DictionaryBuilder<V> {
         DictionaryBuilder {
             dawg,
             units: Default::default(),
@@ -160,23 +1072,59 @@ impl DictionaryBuilder {
             link_table: Default::default(),
             unfixed_index: 0,
             num_unused_nuts: 0,
+            is_arranged: None,
+        }
+    }
+
+    /// Bytes of heap memory held by the builder so far, the DAWG it consumes included.
+    pub fn heap_size(&self) -> usize {
+        self.dawg.heap_size()
+            + self.units.heap_size()
+            + self.extras.heap_size()
+            + self.labels.heap_size()
+            + self.link_table.capacity() * std::mem::size_of::<BaseType>()
+    }
+
+    /// Number of bytes `write` produces for the dictionary `build` returns.
+    ///
+    /// Lays the units out to find out, leaving `build` nothing but handing them over.
+    pub fn serialized_size(&mut self) -> usize {
+        self.arrange();
+        serialized_size::<V>(self.units.len())
+    }
+
+    pub fn build(mut self) -> Dictionary<Pool<V>> {
+        self.arrange();
+        Dictionary::from_units(self.units)
+    }
+
+    /// Builds units of type `V`, starting over with 64-bit ones if some offset does not fit in them.
+    pub fn build_auto(mut self) -> AutoDictionary<V> {
+        if self.arrange() {
+            AutoDictionary::Compact(Dictionary::from_units(self.units))
+        } else {
+            AutoDictionary::Large(DictionaryBuilder::with_layout(self.dawg).build())
         }
     }
 
-    pub fn build(mut self) -> Dictionary {
+    // Lays out all units once, returning false if some offset did not fit in `V`
+    fn arrange(&mut self) -> bool {
+        if let Some(is_arranged) = self.is_arranged {
+            return is_arranged;
+        }
+        let merging_id_count = usize::try_from(self.dawg.merging_id_count()).unwrap();
+        self.link_table.resize(merging_id_count, 0);
         self.reserve_unit(0);
         self.extra(0).set_is_used();
         self.units[0].set_offset(1);
         self.units[0].set_label(0);
 
-        self.build_dictionary_indexes(0, 0);
+        let is_arranged = self.dawg.transition_count() == 0 || self.build_dictionary_indexes(0, 0);
 
         self.fix_all_blocks();
 
-        Dictionary {
-            root: 0,
-            units: self.units,
-        }
+        self.is_arranged = Some(is_arranged);
+        is_arranged
     }
 
     fn build_dictionary_indexes(&mut self, dawg_index: BaseType, dic_index: BaseType) -> bool {
@@ -186,10 +1134,11 @@ impl DictionaryBuilder {
 
         let dawg_child_index = self.dawg.child(dawg_index);
         if self.dawg.is_merging(dawg_child_index) {
-            let offset = self.link_table.get(&dawg_child_index);
-            if let Some(offset) = offset {
+            let merging_id = usize::try_from(self.dawg.merging_id(dawg_child_index)).unwrap();
+            let offset = self.link_table[merging_id];
+            if offset != 0 {
                 let offset = offset ^ dic_index;
-                if (offset & UPPER_MASK == 0) || (offset & LOWER_MASK == 0) {
+                if V::fits_offset(offset) {
                     if self.dawg.is_leaf(dawg_child_index) {
                         self.units[dic_index].set_has_leaf();
                     }
@@ -205,7 +1154,8 @@ impl DictionaryBuilder {
         }
 
         if self.dawg.is_merging(dawg_child_index) {
-            self.link_table.insert(dawg_child_index, offset);
+            let merging_id = usize::try_from(self.dawg.merging_id(dawg_child_index)).unwrap();
+            self.link_table[merging_id] = offset;
         }
 
         let mut dawg_child_index = dawg_child_index;
@@ -346,7 +1296,7 @@ impl DictionaryBuilder {
         }
 
         let relative_offset = index ^ offset;
-        if (relative_offset & LOWER_MASK != 0) && (relative_offset & UPPER_MASK != 0) {
+        if !V::fits_offset(relative_offset) {
             return false;
         }
 
@@ -371,9 +1321,9 @@ impl DictionaryBuilder {
             self.fix_block(src_num_blocks - 16 * 256);
         }
 
-        self.units.resize(dest_num_units, DictionaryUnit(0));
+        self.units.resize(dest_num_units, V::default());
         self.extras
-            .resize(dest_num_blocks, DictionaryExtra { hi: 0, lo: 0 });
+            .resize(dest_num_blocks, DictionaryExtra::default());
 
         for i in (src_num_units + 1)..dest_num_units {
             self.extra(i - 1).set_next(i);
@@ -402,42 +1352,45 @@ impl DictionaryBuilder {
     }
 }
 
-#[derive(Copy, Clone)]
+// Links are kept at full width, as `LargeDictionary` builds may use every `BaseType` index
+#[derive(Copy, Clone, Default)]
 struct DictionaryExtra {
-    lo: BaseType,
-    hi: BaseType,
+    next: BaseType,
+    prev: BaseType,
+    is_fixed: bool,
+    is_used: bool,
 }
 
 impl DictionaryExtra {
     fn set_is_fixed(&mut self) {
-        self.lo |= 1;
+        self.is_fixed = true;
     }
 
     fn set_next(&mut self, next: BaseType) {
-        self.lo = (self.lo & 1) | (next << 1);
+        self.next = next;
     }
 
     fn set_is_used(&mut self) {
-        self.hi |= 1;
+        self.is_used = true;
     }
 
     fn set_prev(&mut self, prev: BaseType) {
-        self.hi = (self.hi & 1) | (prev << 1);
+        self.prev = prev;
     }
 
     fn is_fixed(&self) -> bool {
-        self.lo & 1 == 1
+        self.is_fixed
     }
 
     fn next(&self) -> BaseType {
-        self.lo >> 1
+        self.next
     }
 
     fn is_used(&self) -> bool {
-        self.hi & 1 == 1
+        self.is_used
     }
 
     fn prev(&self) -> BaseType {
-        self.hi >> 1
+        self.prev
     }
 }
@@ -0,0 +1,65 @@
+use crate::unit::BaseType;
+use std::io::Write;
+
+// Pieces of the Graphviz output shared by `Dawg::to_dot` and `Dictionary::to_dot`.
+// States are `s<index>` circles, values are `v<index>` boxes hanging off their states.
+
+pub(crate) fn write_header<T: Write>(writer: &mut T, root: BaseType) -> std::io::Result<()> {
+    writeln!(writer, "digraph dawg {{")?;
+    writeln!(writer, "  rankdir=LR;")?;
+    writeln!(writer, "  node [shape=circle];")?;
+    writeln!(writer, "  start [shape=point];")?;
+    writeln!(writer, "  start -> s{};", root)
+}
+
+pub(crate) fn write_footer<T: Write>(writer: &mut T) -> std::io::Result<()> {
+    writeln!(writer, "}}")
+}
+
+/// States entered by more than one transition are filled.
+pub(crate) fn write_state<T: Write>(
+    writer: &mut T,
+    state: BaseType,
+    is_merging: bool,
+) -> std::io::Result<()> {
+    if is_merging {
+        writeln!(
+            writer,
+            "  s{} [label=\"{}\", style=filled, fillcolor=lightsalmon];",
+            state, state
+        )
+    } else {
+        writeln!(writer, "  s{} [label=\"{}\"];", state, state)
+    }
+}
+
+pub(crate) fn write_transition<T: Write>(
+    writer: &mut T,
+    from: BaseType,
+    to: BaseType,
+    label: u8,
+) -> std::io::Result<()> {
+    write!(writer, "  s{} -> s{} [label=\"", from, to)?;
+    match label {
+        b'"' | b'\\' => write!(writer, "\\{}", char::from(label))?,
+        0x21..=0x7E => write!(writer, "{}", char::from(label))?,
+        _ => write!(writer, "0x{:02X}", label)?,
+    }
+    writeln!(writer, "\"];")
+}
+
+pub(crate) fn write_value<T: Write>(
+    writer: &mut T,
+    leaf: BaseType,
+    value: BaseType,
+) -> std::io::Result<()> {
+    writeln!(writer, "  v{} [shape=box, label=\"{}\"];", leaf, value)
+}
+
+pub(crate) fn write_value_link<T: Write>(
+    writer: &mut T,
+    state: BaseType,
+    leaf: BaseType,
+) -> std::io::Result<()> {
+    writeln!(writer, "  s{} -> v{} [arrowhead=none];", state, leaf)
+}
@@ -0,0 +1,333 @@
+use crate::dictionary::{Dictionary, DictionaryUnit, Unit, UnitStorage};
+use crate::pool::{BitPool, Pool};
+use crate::unit::BaseType;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+// Guide
+
+/// Labels of the first child and next sibling of every dictionary state, making it
+/// possible to enumerate keys without probing all 256 labels.
+///
+/// Serialized exactly like `Guide::Write` of the C++ dawgdic.
+pub struct Guide {
+    units: Pool<GuideUnit>,
+}
+
+impl Guide {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        read_units(reader).map(|units| Guide { units })
+    }
+
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        write_units(&self.units, writer)
+    }
+
+    pub fn size(&self) -> BaseType {
+        self.units.len()
+    }
+
+    pub fn child(&self, index: BaseType) -> u8 {
+        self.units.get(index).map_or(0, |unit| unit.child)
+    }
+
+    pub fn sibling(&self, index: BaseType) -> u8 {
+        self.units.get(index).map_or(0, |unit| unit.sibling)
+    }
+}
+
+/// Guide whose children are ordered by the largest value reachable through them,
+/// descending, rather than by label.
+///
+/// Serialized exactly like `RankedGuide::Write` of the C++ dawgdic.
+pub struct RankedGuide {
+    units: Pool<GuideUnit>,
+}
+
+impl RankedGuide {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        read_units(reader).map(|units| RankedGuide { units })
+    }
+
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        write_units(&self.units, writer)
+    }
+
+    pub fn size(&self) -> BaseType {
+        self.units.len()
+    }
+
+    pub fn child(&self, index: BaseType) -> u8 {
+        self.units.get(index).map_or(0, |unit| unit.child)
+    }
+
+    pub fn sibling(&self, index: BaseType) -> u8 {
+        self.units.get(index).map_or(0, |unit| unit.sibling)
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct GuideUnit {
+    child: u8,
+    sibling: u8,
+}
+
+fn read_units<T: Read>(reader: &mut T) -> Option<Pool<GuideUnit>> {
+    let size = reader.read_u32::<LittleEndian>().ok()?;
+    Pool::read_from(reader, size, |reader| {
+        let child = reader.read_u8()?;
+        let sibling = reader.read_u8()?;
+        Ok(GuideUnit { child, sibling })
+    })
+}
+
+fn write_units<T: Write>(units: &Pool<GuideUnit>, writer: &mut T) -> std::io::Result<()> {
+    writer.write_u32::<LittleEndian>(units.len())?;
+    for unit in units.iter() {
+        writer.write_u8(unit.child)?;
+        writer.write_u8(unit.sibling)?;
+    }
+    Ok(())
+}
+
+// Builders
+//
+// The C++ builders walk the `Dawg` alongside the dictionary. Children of a dictionary state
+// come out in the same ascending label order, so walking the dictionary alone yields
+// identical guides and works for dictionaries loaded from disk as well.
+
+pub struct GuideBuilder<'a, U = Pool<DictionaryUnit>> {
+    dictionary: &'a Dictionary<U>,
+    units: Pool<GuideUnit>,
+    is_fixed_table: BitPool,
+}
+
+impl<'a, U: UnitStorage> GuideBuilder<'a, U> {
+    pub fn new(dictionary: &'a Dictionary<U>) -> GuideBuilder<'a, U> {
+        GuideBuilder {
+            dictionary,
+            units: Default::default(),
+            is_fixed_table: Default::default(),
+        }
+    }
+
+    pub fn build(mut self) -> Guide {
+        self.units
+            .resize(self.dictionary.size(), GuideUnit::default());
+        self.is_fixed_table.resize(self.dictionary.size(), false);
+        self.build_guide(self.dictionary.root());
+        Guide { units: self.units }
+    }
+
+    fn build_guide(&mut self, dic_index: BaseType) {
+        match self.is_fixed_table.get(dic_index) {
+            Some(false) => self.is_fixed_table.set(dic_index, true),
+            _ => return,
+        }
+
+        let labels = child_labels(self.dictionary, dic_index);
+        let first_label = match labels.first() {
+            Some(&label) => label,
+            None => return,
+        };
+        self.units[dic_index].child = first_label;
+
+        for (i, &label) in labels.iter().enumerate() {
+            let dic_child_index = match self.dictionary.follow(label, dic_index) {
+                Some(dic_child_index) => dic_child_index,
+                None => return,
+            };
+            self.build_guide(dic_child_index);
+            if let Some(&sibling_label) = labels.get(i + 1) {
+                self.units[dic_child_index].sibling = sibling_label;
+            }
+        }
+    }
+}
+
+pub struct RankedGuideBuilder<'a, U = Pool<DictionaryUnit>> {
+    dictionary: &'a Dictionary<U>,
+    units: Pool<GuideUnit>,
+    is_fixed_table: BitPool,
+}
+
+impl<'a, U: UnitStorage> RankedGuideBuilder<'a, U> {
+    pub fn new(dictionary: &'a Dictionary<U>) -> RankedGuideBuilder<'a, U> {
+        RankedGuideBuilder {
+            dictionary,
+            units: Default::default(),
+            is_fixed_table: Default::default(),
+        }
+    }
+
+    pub fn build(mut self) -> RankedGuide {
+        self.units
+            .resize(self.dictionary.size(), GuideUnit::default());
+        self.is_fixed_table.resize(self.dictionary.size(), false);
+        let root = self.dictionary.root();
+        if !child_labels(self.dictionary, root).is_empty() {
+            self.build_ranked_guide(root);
+        }
+        RankedGuide { units: self.units }
+    }
+
+    // Returns the largest value reachable from `dic_index`
+    fn build_ranked_guide(&mut self, dic_index: BaseType) -> Option<BaseType> {
+        match self.is_fixed_table.get(dic_index) {
+            Some(false) => self.is_fixed_table.set(dic_index, true),
+            Some(true) => return self.find_max_value(dic_index),
+            None => return None,
+        }
+
+        // Pairs of label and the largest value behind it, the leaf going first
+        let mut links = Vec::new();
+        if self.dictionary.has_value(dic_index) {
+            links.push((0, self.dictionary.value(dic_index)?));
+        }
+        for label in child_labels(self.dictionary, dic_index) {
+            let dic_child_index = self.dictionary.follow(label, dic_index)?;
+            links.push((label, self.build_ranked_guide(dic_child_index)?));
+        }
+        links.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+
+        let &(first_label, max_value) = links.first()?;
+        self.units[dic_index].child = first_label;
+        let mut dic_child_index = self.follow_without_check(dic_index, first_label);
+        for &(sibling_label, _) in links.iter().skip(1) {
+            let dic_sibling_index = self.follow_without_check(dic_index, sibling_label);
+            if dic_child_index < self.units.len() {
+                self.units[dic_child_index].sibling = sibling_label;
+            }
+            dic_child_index = dic_sibling_index;
+        }
+
+        Some(max_value)
+    }
+
+    fn find_max_value(&self, dic_index: BaseType) -> Option<BaseType> {
+        let mut dic_index = dic_index;
+        for _ in 0..self.dictionary.size() {
+            let child_label = self.units.get(dic_index)?.child;
+            if child_label == 0 {
+                if !self.dictionary.has_value(dic_index) {
+                    return None;
+                }
+                return self.dictionary.value(dic_index);
+            }
+            dic_index = self.dictionary.follow(child_label, dic_index)?;
+        }
+        None
+    }
+
+    // Also resolves the leaf label, which `Dictionary::follow` never matches
+    fn follow_without_check(&self, index: BaseType, label: u8) -> BaseType {
+        let offset = self
+            .dictionary
+            .units()
+            .get(index)
+            .map_or(0, |unit| unit.offset());
+        index ^ offset ^ BaseType::from(label)
+    }
+}
+
+fn child_labels<U: UnitStorage>(dictionary: &Dictionary<U>, index: BaseType) -> Vec<u8> {
+    (1..=0xFF)
+        .filter(|&label| dictionary.follow(label, index).is_some())
+        .collect()
+}
+
+// Completer
+
+/// Enumerates the keys below a dictionary state in lexicographic order using a `Guide`.
+pub struct Completer<'a, U = Pool<DictionaryUnit>> {
+    dictionary: &'a Dictionary<U>,
+    guide: &'a Guide,
+    key: Vec<u8>,
+    index_stack: Vec<BaseType>,
+    is_started: bool,
+}
+
+impl<'a, U: UnitStorage> Completer<'a, U> {
+    pub fn new(dictionary: &'a Dictionary<U>, guide: &'a Guide) -> Completer<'a, U> {
+        Completer {
+            dictionary,
+            guide,
+            key: Vec::new(),
+            index_stack: Vec::new(),
+            is_started: false,
+        }
+    }
+
+    /// Restarts the enumeration from the state at `index`, reached by following `prefix`.
+    ///
+    /// Yielded keys start with `prefix`.
+    pub fn start(&mut self, index: BaseType, prefix: &[u8]) {
+        self.key.clear();
+        self.key.extend_from_slice(prefix);
+        self.index_stack.clear();
+        self.index_stack.push(index);
+        self.is_started = false;
+    }
+
+    fn advance(&mut self) -> Option<BaseType> {
+        let mut index = *self.index_stack.last()?;
+
+        if self.is_started {
+            let child_label = self.guide.child(index);
+            if child_label != 0 {
+                index = self.follow(child_label, index)?;
+            } else {
+                loop {
+                    let sibling_label = self.guide.sibling(index);
+                    // Moves to the previous state
+                    if self.index_stack.len() > 1 {
+                        self.key.pop();
+                    }
+                    self.index_stack.pop();
+                    index = *self.index_stack.last()?;
+                    if sibling_label != 0 {
+                        index = self.follow(sibling_label, index)?;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.find_terminal(index)
+    }
+
+    fn follow(&mut self, label: u8, index: BaseType) -> Option<BaseType> {
+        if !self.dictionary.can_extend_key(self.key.len()) {
+            return None;
+        }
+        let index = self.dictionary.follow(label, index)?;
+        self.key.push(label);
+        self.index_stack.push(index);
+        Some(index)
+    }
+
+    fn find_terminal(&mut self, index: BaseType) -> Option<BaseType> {
+        let mut index = index;
+        while !self.dictionary.has_value(index) {
+            let label = self.guide.child(index);
+            index = self.follow(label, index)?;
+        }
+        self.is_started = true;
+        self.dictionary.value(index)
+    }
+}
+
+impl<U: UnitStorage> Iterator for Completer<'_, U> {
+    type Item = (Vec<u8>, BaseType);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance() {
+            Some(value) => Some((self.key.clone(), value)),
+            None => {
+                // Either exhausted or the guide does not match the dictionary
+                self.index_stack.clear();
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::unit::BaseType;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// Leading bytes of a file with a header.
+///
+/// Read as a legacy unit count this would exceed any addressable dictionary,
+/// so headerless files are never mistaken for headed ones.
+pub const MAGIC: [u8; 4] = *b"DAWG";
+
+pub const VERSION: u32 = 1;
+
+/// Units are stored deflated, see `Dictionary::write_compressed`.
+pub const FLAG_COMPRESSED: u32 = 1;
+
+/// Units are 64 bits wide, see `LargeDictionary`.
+pub const FLAG_LARGE_UNITS: u32 = 2;
+
+/// Bits of `Header::flags` understood by this build of the library.
+#[cfg(feature = "compression")]
+pub const KNOWN_FLAGS: u32 = FLAG_COMPRESSED | FLAG_LARGE_UNITS;
+#[cfg(not(feature = "compression"))]
+pub const KNOWN_FLAGS: u32 = FLAG_LARGE_UNITS;
+
+/// Describes the payload following it: the legacy unit count and units, unless flagged otherwise.
+///
+/// The checksum always covers the legacy payload, whichever way it is stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u32,
+    pub flags: u32,
+    pub key_count: BaseType,
+    pub checksum: u32,
+}
+
+impl Header {
+    pub const SIZE: usize = 20;
+
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_u32::<LittleEndian>(self.version)?;
+        writer.write_u32::<LittleEndian>(self.flags)?;
+        writer.write_u32::<LittleEndian>(self.key_count)?;
+        writer.write_u32::<LittleEndian>(self.checksum)
+    }
+
+    /// Reads the remainder of a header whose magic has already been consumed.
+    ///
+    /// Headers of other versions or with unknown flags are rejected.
+    pub fn read_after_magic<T: Read>(reader: &mut T) -> Option<Header> {
+        let header = Header {
+            version: reader.read_u32::<LittleEndian>().ok()?,
+            flags: reader.read_u32::<LittleEndian>().ok()?,
+            key_count: reader.read_u32::<LittleEndian>().ok()?,
+            checksum: reader.read_u32::<LittleEndian>().ok()?,
+        };
+        if header.version != VERSION || header.flags & !KNOWN_FLAGS != 0 {
+            return None;
+        }
+        Some(header)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Header> {
+        let mut reader = bytes.get(..Self::SIZE)?;
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic).ok()?;
+        if magic != MAGIC {
+            return None;
+        }
+        Header::read_after_magic(&mut reader)
+    }
+}
+
+pub fn checksum(payload: &[u8]) -> u32 {
+    crc32fast::hash(payload)
+}
+
+/// Passes reads through while computing the checksum of everything read.
+pub struct ChecksumReader<'a, T: Read> {
+    inner: &'a mut T,
+    hasher: crc32fast::Hasher,
+}
+
+impl<'a, T: Read> ChecksumReader<'a, T> {
+    pub fn new(inner: &'a mut T) -> ChecksumReader<'a, T> {
+        ChecksumReader {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    pub fn checksum(self) -> u32 {
+        self.hasher.finalize()
+    }
+}
+
+impl<T: Read> Read for ChecksumReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
@@ -1,6 +1,21 @@
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "tokio")]
+mod async_io;
+pub mod automaton;
+mod base64;
+pub mod bytes_dictionary;
+#[cfg(feature = "compression")]
+mod compression;
 pub mod dawg;
 pub mod dictionary;
+mod dot;
+pub mod guide;
+pub mod header;
+pub mod payload;
 pub mod pool;
+pub mod python;
+pub mod record_dictionary;
+#[cfg(feature = "serde")]
+mod serde_impl;
 pub mod unit;
@@ -0,0 +1,225 @@
+//! Dictionaries mapping keys to values wider than a leaf unit.
+//!
+//! Leaves hold 31-bit values. A `PayloadDictionary` stores a payload in its leaf when it
+//! fits in `INLINE_LIMIT`, and otherwise puts it in a side table, with the leaf holding
+//! `TABLE_BIT` plus its index there. Equal payloads are stored once.
+
+use crate::automaton::AlwaysMatch;
+use crate::dawg::DawgBuilder;
+use crate::dictionary::{Dictionary, DictionaryBuilder};
+use crate::unit::BaseType;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+/// Leaf values at or above this refer to the side table.
+pub const INLINE_LIMIT: BaseType = 1 << 30;
+
+/// Set in leaf values holding a side table index.
+pub const TABLE_BIT: BaseType = INLINE_LIMIT;
+
+/// Values a `PayloadDictionary` can map keys to.
+pub trait Payload: Clone {
+    /// The payload as a number below `INLINE_LIMIT`, if it can be stored in its leaf.
+    fn to_inline(&self) -> Option<BaseType> {
+        None
+    }
+
+    /// Reverses `to_inline`.
+    fn from_inline(_value: BaseType) -> Option<Self> {
+        None
+    }
+
+    /// Appends the side table entry for the payload.
+    fn encode(&self, output: &mut Vec<u8>);
+
+    /// Reverses `encode`, given exactly the bytes it appended.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Payload for u32 {
+    fn to_inline(&self) -> Option<BaseType> {
+        Some(*self).filter(|&value| value < INLINE_LIMIT)
+    }
+
+    fn from_inline(value: BaseType) -> Option<Self> {
+        Some(value)
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.to_le_bytes())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl Payload for u64 {
+    fn to_inline(&self) -> Option<BaseType> {
+        BaseType::try_from(*self)
+            .ok()
+            .filter(|&value| value < INLINE_LIMIT)
+    }
+
+    fn from_inline(value: BaseType) -> Option<Self> {
+        Some(u64::from(value))
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.to_le_bytes())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl Payload for Vec<u8> {
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl Payload for String {
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(self.as_bytes())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+/// Dictionary whose keys map to payloads of type `P`.
+pub struct PayloadDictionary<P> {
+    dictionary: Dictionary,
+    table: Vec<P>,
+}
+
+impl<P: Payload> PayloadDictionary<P> {
+    /// Reads a dictionary written by `write`.
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        let dictionary = Dictionary::from_reader(reader)?;
+        let table_len = reader.read_u32::<LittleEndian>().ok()?;
+        let mut table = Vec::new();
+        let mut bytes = Vec::new();
+        for _ in 0..table_len {
+            let len = reader.read_u32::<LittleEndian>().ok()?;
+            bytes.clear();
+            reader.take(u64::from(len)).read_to_end(&mut bytes).ok()?;
+            if bytes.len() != usize::try_from(len).ok()? {
+                return None;
+            }
+            table.push(P::decode(&bytes)?);
+        }
+        Some(PayloadDictionary { dictionary, table })
+    }
+
+    /// Writes the dictionary with a header, followed by the side table: its length, then
+    /// every entry as its length and encoded bytes.
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.dictionary.write_with_header(writer)?;
+        writer.write_u32::<LittleEndian>(BaseType::try_from(self.table.len()).unwrap())?;
+        let mut bytes = Vec::new();
+        for payload in &self.table {
+            bytes.clear();
+            payload.encode(&mut bytes);
+            writer.write_u32::<LittleEndian>(BaseType::try_from(bytes.len()).unwrap())?;
+            writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    /// Number of distinct payloads kept in the side table rather than in leaves.
+    pub fn table_len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.dictionary.contains(key)
+    }
+
+    pub fn find(&self, key: &[u8]) -> Option<P> {
+        self.payload(self.dictionary.find(key)?)
+    }
+
+    /// Every key with its payload, in lexicographic order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, P)> + '_ {
+        self.dictionary
+            .search(AlwaysMatch)
+            .filter_map(move |(key, value)| Some((key, self.payload(value)?)))
+    }
+
+    fn payload(&self, value: BaseType) -> Option<P> {
+        if value & TABLE_BIT == 0 {
+            return P::from_inline(value);
+        }
+        let index = usize::try_from(value & !TABLE_BIT).ok()?;
+        self.table.get(index).cloned()
+    }
+}
+
+/// Builds a `PayloadDictionary` from keys inserted in lexicographic order.
+///
+/// Payloads encoding to the same bytes share a side table entry, and so a leaf value,
+/// which lets the DAWG merge the states leading to them as well.
+pub struct PayloadDawgBuilder<P> {
+    builder: DawgBuilder,
+    table: Vec<P>,
+    // Side table indexes by encoded payload
+    table_ids: HashMap<Vec<u8>, BaseType>,
+}
+
+impl<P: Payload> Default for PayloadDawgBuilder<P> {
+    fn default() -> Self {
+        PayloadDawgBuilder::new()
+    }
+}
+
+impl<P: Payload> PayloadDawgBuilder<P> {
+    pub fn new() -> PayloadDawgBuilder<P> {
+        PayloadDawgBuilder {
+            builder: DawgBuilder::new(),
+            table: Vec::new(),
+            table_ids: HashMap::new(),
+        }
+    }
+
+    /// Fails like `DawgBuilder::insert_key`, or when the side table is full.
+    #[allow(clippy::result_unit_err)]
+    pub fn insert_key(&mut self, key: &str, payload: P) -> Result<(), ()> {
+        if let Some(value) = payload.to_inline().filter(|&value| value < INLINE_LIMIT) {
+            return self.builder.insert_key(key, value);
+        }
+        let mut bytes = Vec::new();
+        payload.encode(&mut bytes);
+        if let Some(&index) = self.table_ids.get(&bytes) {
+            return self.builder.insert_key(key, TABLE_BIT | index);
+        }
+        let index = BaseType::try_from(self.table.len()).map_err(|_| ())?;
+        if index >= INLINE_LIMIT {
+            return Err(());
+        }
+        self.builder.insert_key(key, TABLE_BIT | index)?;
+        self.table.push(payload);
+        self.table_ids.insert(bytes, index);
+        Ok(())
+    }
+
+    pub fn build(self) -> PayloadDictionary<P> {
+        PayloadDictionary {
+            dictionary: DictionaryBuilder::new(self.builder.build()).build(),
+            table: self.table,
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::unit::BaseType;
 use std::convert::TryFrom;
+use std::io::Read;
 use std::ops::{Index, IndexMut};
+use std::sync::OnceLock;
 
 #[derive(Clone)]
 pub struct Pool<T: Clone> {
@@ -44,9 +46,30 @@ impl<T: Clone> Pool<T> {
         self.inner.resize(usize::try_from(size).unwrap(), value)
     }
 
+    /// Bytes allocated for the elements, including spare capacity.
+    pub fn heap_size(&self) -> usize {
+        self.inner.capacity() * std::mem::size_of::<T>()
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, T> {
         self.inner.iter()
     }
+
+    // Reads `size` elements with `read_element`, failing if the data runs out first.
+    //
+    // Sizes come from the data being read, so a corrupt or crafted one can be huge. The pool
+    // grows as elements arrive instead of being allocated for `size` up front.
+    pub(crate) fn read_from<R: Read>(
+        reader: &mut R,
+        size: BaseType,
+        mut read_element: impl FnMut(&mut R) -> std::io::Result<T>,
+    ) -> Option<Pool<T>> {
+        let mut inner = Vec::new();
+        for _ in 0..size {
+            inner.push(read_element(reader).ok()?);
+        }
+        Some(Pool { inner })
+    }
 }
 
 impl<T: Clone> Index<BaseType> for Pool<T> {
@@ -63,3 +86,156 @@ impl<T: Clone> IndexMut<BaseType> for Pool<T> {
         &mut self.inner[index]
     }
 }
+
+// Words covered by one entry of the rank directory
+const RANK_BLOCK_WORDS: usize = 8;
+
+/// Bits packed 32 to a word, answering rank queries.
+///
+/// Ranks are looked up in a directory with a count per 256 bits, built on the first
+/// `rank` after a modification.
+#[derive(Clone, Default)]
+pub struct BitPool {
+    words: Vec<u32>,
+    len: BaseType,
+    ranks: OnceLock<Vec<BaseType>>,
+}
+
+impl BitPool {
+    /// Takes `len` bits packed least significant first, as written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8], len: BaseType) -> Option<BitPool> {
+        let bytes = bytes.get(..usize::try_from(len).ok()?.div_ceil(8))?;
+        let words = bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            })
+            .collect();
+        let mut pool = BitPool {
+            words,
+            len,
+            ranks: OnceLock::new(),
+        };
+        pool.clear_padding();
+        Some(pool)
+    }
+
+    /// Packs the bits least significant first into as few bytes as they take.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        bytes.truncate(usize::try_from(self.len).unwrap().div_ceil(8));
+        bytes
+    }
+
+    pub fn get(&self, index: BaseType) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        let word = self.words[usize::try_from(index / 32).unwrap()];
+        Some(word & (1 << (index % 32)) != 0)
+    }
+
+    pub fn set(&mut self, index: BaseType, bit: bool) {
+        assert!(index < self.len, "bit index out of bounds");
+        let word = &mut self.words[usize::try_from(index / 32).unwrap()];
+        if bit {
+            *word |= 1 << (index % 32);
+        } else {
+            *word &= !(1 << (index % 32));
+        }
+        self.ranks.take();
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(32) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, bit);
+    }
+
+    pub fn len(&self) -> BaseType {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+        self.ranks.take();
+    }
+
+    pub fn resize(&mut self, size: BaseType, bit: bool) {
+        let fill = if bit { u32::MAX } else { 0 };
+        if size > self.len && !self.len.is_multiple_of(32) {
+            let last = self.words.len() - 1;
+            let padding = u32::MAX << (self.len % 32);
+            self.words[last] = (self.words[last] & !padding) | (fill & padding);
+        }
+        self.words
+            .resize(usize::try_from(size).unwrap().div_ceil(32), fill);
+        self.len = size;
+        self.clear_padding();
+        self.ranks.take();
+    }
+
+    /// Number of set bits before `index`, or in the whole pool when `index` is past its end.
+    pub fn rank(&self, index: BaseType) -> BaseType {
+        let index = usize::try_from(index.min(self.len)).unwrap();
+        let ranks = self.ranks.get_or_init(|| {
+            let mut rank = 0;
+            self.words
+                .chunks(RANK_BLOCK_WORDS)
+                .map(|block| {
+                    let block_rank = rank;
+                    rank += block.iter().map(|word| word.count_ones()).sum::<BaseType>();
+                    block_rank
+                })
+                .collect()
+        });
+        let word_index = index / 32;
+        let block_begin = word_index - word_index % RANK_BLOCK_WORDS;
+        let mut rank = ranks
+            .get(word_index / RANK_BLOCK_WORDS)
+            .copied()
+            .unwrap_or(0);
+        rank += self.words[block_begin..word_index]
+            .iter()
+            .map(|word| word.count_ones())
+            .sum::<BaseType>();
+        if index % 32 != 0 {
+            rank += (self.words[word_index] & ((1 << (index % 32)) - 1)).count_ones();
+        }
+        rank
+    }
+
+    pub fn count_ones(&self) -> BaseType {
+        self.rank(self.len)
+    }
+
+    /// Bytes allocated for the bits, including spare capacity and the rank directory.
+    pub fn heap_size(&self) -> usize {
+        self.words.capacity() * std::mem::size_of::<u32>()
+            + self.ranks.get().map_or(0, |ranks| {
+                ranks.capacity() * std::mem::size_of::<BaseType>()
+            })
+    }
+
+    // Bits past the end stay unset, so that they never count towards ranks
+    fn clear_padding(&mut self) {
+        if !self.len.is_multiple_of(32) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << (self.len % 32)) - 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Readers for files saved by the Python `DAWG` package.
+//!
+//! `DAWG` and `IntDAWG` save a bare dictionary, readable with `Dictionary::from_reader`.
+//! `CompletionDAWG` and `IntCompletionDAWG` append a guide to it. `BytesDAWG` and
+//! `RecordDAWG` are completion DAWGs whose keys embed their values as
+//! `utf8(key) + separator + base64(value)`.
+
+use crate::dictionary::Dictionary;
+use crate::guide::{Completer, Guide};
+use std::io::{Read, Write};
+
+pub use crate::bytes_dictionary::BytesDictionary;
+
+/// Separator between keys and encoded values used by `BytesDAWG` unless overridden.
+pub const PAYLOAD_SEPARATOR: u8 = 0x01;
+
+/// `CompletionDAWG` or `IntCompletionDAWG`: a dictionary followed by its guide.
+pub struct CompletionDawg {
+    dictionary: Dictionary,
+    guide: Guide,
+}
+
+impl CompletionDawg {
+    pub fn new(dictionary: Dictionary, guide: Guide) -> CompletionDawg {
+        CompletionDawg { dictionary, guide }
+    }
+
+    pub fn from_reader<T: Read>(reader: &mut T) -> Option<Self> {
+        let dictionary = Dictionary::from_reader(reader)?;
+        let guide = Guide::from_reader(reader)?;
+        Some(CompletionDawg { dictionary, guide })
+    }
+
+    /// Writes the file `CompletionDAWG.load` reads.
+    pub fn write<T: Write>(&self, writer: &mut T) -> std::io::Result<()> {
+        self.dictionary.write(writer)?;
+        self.guide.write(writer)
+    }
+
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    pub fn guide(&self) -> &Guide {
+        &self.guide
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.dictionary.contains(key)
+    }
+
+    /// Keys starting with `prefix` along with their values, in lexicographic order.
+    pub fn complete(&self, prefix: &[u8]) -> Completer<'_> {
+        let mut completer = Completer::new(&self.dictionary, &self.guide);
+        if let Some(index) = self.dictionary.follow_bytes(prefix, self.dictionary.root()) {
+            completer.start(index, prefix);
+        }
+        completer
+    }
+}
+
+/// `BytesDAWG` or `RecordDAWG`: keys mapped to any number of byte strings.
+///
+/// `RecordDAWG` values are `struct.pack`ed records, returned here as raw bytes; see
+/// `RecordDictionary` to decode them.
+pub type BytesDawg = BytesDictionary;
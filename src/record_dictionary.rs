@@ -0,0 +1,173 @@
+//! Keys mapped to any number of fixed-layout records.
+//!
+//! Records are encoded with `Record` and stored as the values of a `BytesDictionary`, which
+//! is how the Python `DAWG` package stores its `RecordDAWG`. Integers are big-endian, as
+//! with a `struct` format starting with `>`, so `RecordDAWG(">IHH")` reads as
+//! `RecordDictionary<(u32, u16, u16)>`.
+
+use crate::bytes_dictionary::{BytesDictionary, BytesDictionaryBuilder};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+/// Codec for records of `SIZE` bytes each.
+pub trait Record: Sized {
+    const SIZE: usize;
+
+    /// Appends exactly `SIZE` bytes.
+    fn encode(&self, output: &mut Vec<u8>);
+
+    /// Reverses `encode`, given exactly `SIZE` bytes.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_record_for_number {
+    ($($number:ty),*) => {
+        $(
+            impl Record for $number {
+                const SIZE: usize = std::mem::size_of::<$number>();
+
+                fn encode(&self, output: &mut Vec<u8>) {
+                    output.extend_from_slice(&self.to_be_bytes())
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    Some(<$number>::from_be_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_record_for_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+macro_rules! impl_record_for_tuple {
+    ($(($($field:ident),+)),*) => {
+        $(
+            impl<$($field: Record),+> Record for ($($field,)+) {
+                const SIZE: usize = 0 $(+ $field::SIZE)+;
+
+                #[allow(non_snake_case)]
+                fn encode(&self, output: &mut Vec<u8>) {
+                    let ($($field,)+) = self;
+                    $($field.encode(output);)+
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    if bytes.len() != Self::SIZE {
+                        return None;
+                    }
+                    let mut position = 0;
+                    Some(($({
+                        position += $field::SIZE;
+                        $field::decode(&bytes[position - $field::SIZE..position])?
+                    },)+))
+                }
+            }
+        )*
+    };
+}
+
+impl_record_for_tuple!((A), (A, B), (A, B, C), (A, B, C, D), (A, B, C, D, E));
+
+/// Dictionary whose keys map to any number of records of type `T`.
+pub struct RecordDictionary<T> {
+    bytes: BytesDictionary,
+    records: PhantomData<T>,
+}
+
+impl<T: Record> RecordDictionary<T> {
+    pub fn from_reader<R: Read>(reader: &mut R) -> Option<Self> {
+        BytesDictionary::from_reader(reader).map(RecordDictionary::from_bytes_dictionary)
+    }
+
+    /// Reads a file built with a custom `payload_separator`.
+    pub fn from_reader_with_separator<R: Read>(reader: &mut R, separator: u8) -> Option<Self> {
+        BytesDictionary::from_reader_with_separator(reader, separator)
+            .map(RecordDictionary::from_bytes_dictionary)
+    }
+
+    /// Views the values of `bytes` as records; values of another size are skipped.
+    pub fn from_bytes_dictionary(bytes: BytesDictionary) -> Self {
+        RecordDictionary {
+            bytes,
+            records: PhantomData,
+        }
+    }
+
+    /// Writes the file `RecordDAWG.load` reads.
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.bytes.write(writer)
+    }
+
+    pub fn bytes_dictionary(&self) -> &BytesDictionary {
+        &self.bytes
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.bytes.contains(key)
+    }
+
+    /// All records stored for `key`, empty when there are none, in the order of
+    /// `BytesDictionary::get`.
+    pub fn get(&self, key: &[u8]) -> Vec<T> {
+        self.bytes
+            .get(key)
+            .iter()
+            .filter_map(|value| T::decode(value))
+            .collect()
+    }
+
+    /// Pairs of keys starting with `prefix` and their records, in the order of
+    /// `BytesDictionary::items`.
+    pub fn items<'a>(&'a self, prefix: &[u8]) -> impl Iterator<Item = (Vec<u8>, T)> + 'a {
+        self.bytes
+            .items(prefix)
+            .filter_map(|(key, value)| Some((key, T::decode(&value)?)))
+    }
+}
+
+/// Collects keys and records in any order, then builds a `RecordDictionary` of them.
+pub struct RecordDictionaryBuilder<T> {
+    bytes: BytesDictionaryBuilder,
+    records: PhantomData<T>,
+}
+
+impl<T: Record> Default for RecordDictionaryBuilder<T> {
+    fn default() -> Self {
+        RecordDictionaryBuilder::new()
+    }
+}
+
+impl<T: Record> RecordDictionaryBuilder<T> {
+    pub fn new() -> RecordDictionaryBuilder<T> {
+        RecordDictionaryBuilder::from_bytes_builder(BytesDictionaryBuilder::new())
+    }
+
+    /// # Panics
+    ///
+    /// If `separator` is 0, which terminates keys.
+    pub fn with_separator(separator: u8) -> RecordDictionaryBuilder<T> {
+        RecordDictionaryBuilder::from_bytes_builder(BytesDictionaryBuilder::with_separator(
+            separator,
+        ))
+    }
+
+    fn from_bytes_builder(bytes: BytesDictionaryBuilder) -> RecordDictionaryBuilder<T> {
+        RecordDictionaryBuilder {
+            bytes,
+            records: PhantomData,
+        }
+    }
+
+    /// Adds `record` to the records of `key`, failing like `BytesDictionaryBuilder::insert`.
+    #[allow(clippy::result_unit_err)]
+    pub fn insert(&mut self, key: &[u8], record: &T) -> Result<(), ()> {
+        let mut value = Vec::with_capacity(T::SIZE);
+        record.encode(&mut value);
+        self.bytes.insert(key, &value)
+    }
+
+    pub fn build(self) -> RecordDictionary<T> {
+        RecordDictionary::from_bytes_dictionary(self.bytes.build())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::dawg::Dawg;
+use crate::dictionary::{Dictionary, DictionaryUnit, UnitStorage};
+use crate::guide::{Guide, RankedGuide};
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::io::Read;
+
+// Everything is serialized as a single byte string holding the same bytes as `write`,
+// which binary formats store as is
+
+impl<U: UnitStorage<Unit = DictionaryUnit>> Serialize for Dictionary<U> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        write_bytes(serializer, |writer| self.write(writer))
+    }
+}
+
+impl<'de> Deserialize<'de> for Dictionary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        read_exactly(
+            deserializer,
+            |reader| Dictionary::from_reader(reader),
+            "dictionary",
+        )
+    }
+}
+
+impl Serialize for Guide {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        write_bytes(serializer, |writer| self.write(writer))
+    }
+}
+
+impl<'de> Deserialize<'de> for Guide {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        read_exactly(deserializer, |reader| Guide::from_reader(reader), "guide")
+    }
+}
+
+impl Serialize for RankedGuide {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        write_bytes(serializer, |writer| self.write(writer))
+    }
+}
+
+impl<'de> Deserialize<'de> for RankedGuide {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        read_exactly(
+            deserializer,
+            |reader| RankedGuide::from_reader(reader),
+            "ranked guide",
+        )
+    }
+}
+
+impl Serialize for Dawg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        write_bytes(serializer, |writer| self.write(writer))
+    }
+}
+
+impl<'de> Deserialize<'de> for Dawg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        read_exactly(deserializer, |reader| Dawg::from_reader(reader), "dawg")
+    }
+}
+
+// Serializes what `write` writes as a byte string
+fn write_bytes<S, F>(serializer: S, write: F) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    F: FnOnce(&mut Vec<u8>) -> std::io::Result<()>,
+{
+    let mut data_buf = Vec::new();
+    write(&mut data_buf).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_bytes(&data_buf)
+}
+
+// Reads a value from the whole of a deserialized byte string
+fn read_exactly<'de, D, T, F>(deserializer: D, from_reader: F, name: &str) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&mut &[u8]) -> Option<T>,
+{
+    let data_buf = deserializer.deserialize_byte_buf(ByteBufVisitor)?;
+    let mut reader = &data_buf[..];
+    let value = from_reader(&mut reader)
+        .ok_or_else(|| D::Error::custom(format_args!("invalid {}", name)))?;
+    if reader.read(&mut [0]).map_err(D::Error::custom)? != 0 {
+        return Err(D::Error::custom(format_args!(
+            "trailing bytes after {}",
+            name
+        )));
+    }
+    Ok(value)
+}
+
+struct ByteBufVisitor;
+
+impl<'de> Visitor<'de> for ByteBufVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(value)
+    }
+
+    // Self-describing formats without a byte string type fall back to a sequence of bytes
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut value = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            value.push(byte);
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use common::{build_dictionary, load_test_corpus};
+use dawgdic::dictionary::Dictionary;
+use dawgdic::guide::{Guide, GuideBuilder};
+use std::io::Cursor;
+
+#[tokio::test]
+async fn loads_and_saves_dictionary_asynchronously() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let mut async_buf: Vec<u8> = Vec::new();
+    dictionary.write_async(&mut async_buf).await.unwrap();
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    assert_eq!(async_buf, data_buf);
+
+    let mut async_buf: Vec<u8> = Vec::new();
+    dictionary
+        .write_with_header_async(&mut async_buf)
+        .await
+        .unwrap();
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write_with_header(&mut data_buf).unwrap();
+    assert_eq!(async_buf, data_buf);
+
+    // Whatever follows the dictionary is left in the stream
+    GuideBuilder::new(&dictionary)
+        .build()
+        .write(&mut data_buf)
+        .unwrap();
+    let mut reader = Cursor::new(data_buf);
+    let new_dictionary = Dictionary::from_async_reader(&mut reader).await.unwrap();
+    let guide = Guide::from_reader(&mut reader).unwrap();
+    assert_eq!(guide.size(), new_dictionary.size());
+
+    corpus.iter().for_each(|(key, value)| {
+        assert_eq!(new_dictionary.find(key.as_bytes()), Some(*value));
+    });
+
+    let mut corrupted = async_buf.clone();
+    corrupted[100] ^= 0x10;
+    assert!(Dictionary::from_async_reader(&mut Cursor::new(corrupted))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn rejects_unit_counts_beyond_the_data() {
+    let huge_count = [0xFF, 0xFF, 0xFF, 0x7F];
+    assert!(Dictionary::from_async_reader(&mut Cursor::new(huge_count))
+        .await
+        .is_none());
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn loads_compressed_dictionary_asynchronously() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write_compressed(&mut data_buf).unwrap();
+    let compressed_len = data_buf.len();
+    data_buf.extend_from_slice(b"rest");
+
+    let mut reader = Cursor::new(&data_buf);
+    let new_dictionary = Dictionary::from_async_reader(&mut reader).await.unwrap();
+    assert_eq!(reader.position() as usize, compressed_len);
+    corpus.iter().for_each(|(key, value)| {
+        assert_eq!(new_dictionary.find(key.as_bytes()), Some(*value));
+    });
+
+    // Compressed data shorter than its stated length is rejected like `from_reader` does
+    let truncated = &data_buf[..compressed_len - 1];
+    assert!(Dictionary::from_async_reader(&mut Cursor::new(truncated))
+        .await
+        .is_none());
+    assert!(Dictionary::from_reader(&mut Cursor::new(truncated)).is_none());
+}
@@ -0,0 +1,68 @@
+use dawgdic::pool::BitPool;
+
+#[test]
+fn stores_bits_and_answers_ranks() {
+    let bits: Vec<bool> = (0..1000u32)
+        .map(|index| index % 3 == 0 || index % 7 == 0)
+        .collect();
+    let mut pool = BitPool::default();
+    assert!(pool.is_empty());
+    for &bit in &bits {
+        pool.push(bit);
+    }
+    assert_eq!(pool.len(), 1000);
+    assert_eq!(pool.get(1000), None);
+
+    let mut rank = 0;
+    for (index, &bit) in bits.iter().enumerate() {
+        assert_eq!(pool.get(index as u32), Some(bit));
+        assert_eq!(pool.rank(index as u32), rank);
+        rank += u32::from(bit);
+    }
+    assert_eq!(pool.count_ones(), rank);
+    assert_eq!(pool.rank(u32::MAX), rank);
+
+    // Modifications are reflected in later ranks
+    pool.set(0, false);
+    pool.set(1, true);
+    pool.set(998, true);
+    assert_eq!(pool.rank(1), 0);
+    assert_eq!(pool.rank(2), 1);
+    assert_eq!(pool.count_ones(), rank + 1);
+}
+
+#[test]
+fn resizes_and_packs_bits() {
+    let mut pool = BitPool::default();
+    pool.resize(45, true);
+    assert_eq!(pool.count_ones(), 45);
+    pool.resize(40, false);
+    assert_eq!(pool.count_ones(), 40);
+    pool.resize(70, false);
+    assert_eq!(pool.count_ones(), 40);
+    assert_eq!(pool.get(40), Some(false));
+    pool.set(69, true);
+
+    let bytes = pool.to_bytes();
+    assert_eq!(bytes.len(), 9);
+    assert_eq!(bytes[..5], [0xFF; 5]);
+    assert_eq!(bytes[8], 0b0010_0000);
+
+    let new_pool = BitPool::from_bytes(&bytes, 70).unwrap();
+    assert_eq!(new_pool.len(), 70);
+    assert_eq!(new_pool.count_ones(), 41);
+    assert_eq!(new_pool.to_bytes(), bytes);
+
+    // Bits past the length are ignored, and missing bytes rejected
+    assert_eq!(BitPool::from_bytes(&[0xFF], 3).unwrap().count_ones(), 3);
+    assert!(BitPool::from_bytes(&bytes, 80).is_none());
+
+    // A bit pool takes an eighth of the memory of a pool of booleans
+    let mut pool = BitPool::default();
+    pool.resize(1 << 16, false);
+    assert!(pool.heap_size() <= (1 << 13));
+
+    pool.clear();
+    assert!(pool.is_empty());
+    assert_eq!(pool.count_ones(), 0);
+}
@@ -0,0 +1,105 @@
+use dawgdic::bytes_dictionary::{BytesDictionary, BytesDictionaryBuilder};
+use dawgdic::python::BytesDawg;
+use std::io::Cursor;
+
+#[test]
+fn maps_keys_to_many_values() {
+    let mut builder = BytesDictionaryBuilder::new();
+    // Word forms with their morphological tags, inserted in no particular order
+    builder
+        .insert("стали".as_bytes(), b"VERB,past,plur")
+        .unwrap();
+    builder
+        .insert("стали".as_bytes(), b"NOUN,gent,sing")
+        .unwrap();
+    builder
+        .insert("сталь".as_bytes(), b"NOUN,nomn,sing")
+        .unwrap();
+    builder
+        .insert("стали".as_bytes(), b"NOUN,nomn,plur")
+        .unwrap();
+    builder
+        .insert("стали".as_bytes(), b"VERB,past,plur")
+        .unwrap();
+    builder.insert(b"bin", &[0x00, 0xFF, 0x10, 0x01]).unwrap();
+    builder.insert(b"empty", b"").unwrap();
+    builder.insert(b"order", &[0x00]).unwrap();
+    builder.insert(b"order", &[0xFF]).unwrap();
+    assert!(builder.insert(b"sep\x01arated", b"x").is_err());
+    assert!(builder.insert(b"nul\x00", b"x").is_err());
+    let dictionary = builder.build();
+
+    assert_eq!(
+        dictionary.get("стали".as_bytes()),
+        vec![
+            b"NOUN,gent,sing".to_vec(),
+            b"NOUN,nomn,plur".to_vec(),
+            b"VERB,past,plur".to_vec(),
+        ]
+    );
+    assert_eq!(
+        dictionary.get("сталь".as_bytes()),
+        vec![b"NOUN,nomn,sing".to_vec()]
+    );
+    assert_eq!(dictionary.get(b"bin"), vec![vec![0x00, 0xFF, 0x10, 0x01]]);
+    // Values are ordered by their base64 encodings, "/w==" before "AA=="
+    assert_eq!(dictionary.get(b"order"), vec![vec![0xFF], vec![0x00]]);
+    assert_eq!(dictionary.get(b"empty"), vec![Vec::<u8>::new()]);
+    assert!(dictionary.get("стал".as_bytes()).is_empty());
+    assert!(dictionary.contains("сталь".as_bytes()));
+    assert!(!dictionary.contains("стал".as_bytes()));
+
+    let keys: Vec<Vec<u8>> = dictionary
+        .items("стал".as_bytes())
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys.len(), 4);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let new_dictionary = BytesDawg::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert_eq!(
+        new_dictionary.items(b"").collect::<Vec<_>>(),
+        dictionary.items(b"").collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn encodes_values_like_python() {
+    // `BytesDAWG([("bar", b"\x00\xff\x10"), ("foo", b"f"), ("foo", b"foo"), ("foobar", b"")])`
+    let mut builder = BytesDictionaryBuilder::new();
+    builder.insert(b"bar", &[0x00, 0xFF, 0x10]).unwrap();
+    builder.insert(b"foo", b"f").unwrap();
+    builder.insert(b"foo", b"foo").unwrap();
+    builder.insert(b"foobar", b"").unwrap();
+    let dictionary = builder.build();
+
+    let keys: Vec<Vec<u8>> = dictionary
+        .completion()
+        .complete(b"")
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            b"bar\x01AP8Q\n".to_vec(),
+            b"foo\x01Zg==\n".to_vec(),
+            b"foo\x01Zm9v\n".to_vec(),
+            b"foobar\x01\n".to_vec(),
+        ]
+    );
+}
+
+#[test]
+fn uses_custom_separator() {
+    let mut builder = BytesDictionaryBuilder::with_separator(b'|');
+    builder.insert(b"key", b"value").unwrap();
+    let dictionary = builder.build();
+    assert_eq!(dictionary.get(b"key"), vec![b"value".to_vec()]);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let new_dictionary =
+        BytesDictionary::from_reader_with_separator(&mut Cursor::new(&data_buf), b'|').unwrap();
+    assert_eq!(new_dictionary.get(b"key"), vec![b"value".to_vec()]);
+}
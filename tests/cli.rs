@@ -0,0 +1,121 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+#[test]
+fn builds_and_queries_dictionary_file() {
+    let corpus_file_path = corpus_file_path();
+    let dictionary_path = temp_path("smoketest.dic");
+
+    let output = dawgdic(&[
+        "build",
+        path_str(&corpus_file_path),
+        path_str(&dictionary_path),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "999 keys, 4352 units\n");
+
+    let dictionary = path_str(&dictionary_path);
+
+    let output = dawgdic(&["find", dictionary, "act", "annulment", "this"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("act\t510473\nthis\t"));
+
+    let output = dawgdic(&["contains", dictionary, "this", "loremaster"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "this\ttrue\nloremaster\tfalse\n");
+
+    let output = dawgdic(&["prefix", dictionary, "actions"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "act\t510473\naction\t319887\n");
+
+    let output = dawgdic(&["complete", dictionary, "act", "--limit", "3"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "act\t510473\naction\t319887\nactive\t296846\n"
+    );
+
+    let output = dawgdic(&["dump", dictionary]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        std::fs::read_to_string(&corpus_file_path).unwrap()
+    );
+
+    let output = dawgdic(&["stats", dictionary]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("keys\t999\n"));
+    assert!(stdout(&output).contains("used units\t4083\n"));
+    assert!(stdout(&output).contains("validation\tok\n"));
+}
+
+#[test]
+fn reads_keys_from_standard_input() {
+    let dictionary_path = temp_path("stdin.dic");
+    let output = dawgdic(&[
+        "build",
+        "--header",
+        path_str(&corpus_file_path()),
+        path_str(&dictionary_path),
+    ]);
+    assert!(output.status.success());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dawgdic"))
+        .args(["find", path_str(&dictionary_path)])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"act\naction\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "act\t510473\naction\t319887\n");
+}
+
+#[test]
+fn reports_bad_input() {
+    let input_path = temp_path("unsorted.txt");
+    std::fs::write(&input_path, "b\t1\na\t2\n").unwrap();
+    let output = dawgdic(&[
+        "build",
+        path_str(&input_path),
+        path_str(&temp_path("unsorted.dic")),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unsorted.txt:2"));
+
+    let output = dawgdic(&["find", path_str(&input_path), "a"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a dictionary"));
+}
+
+fn dawgdic(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dawgdic"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+fn corpus_file_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("corpora/01_dawg_smoketest.txt")
+}
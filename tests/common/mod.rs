@@ -0,0 +1,54 @@
+// Helpers shared by the integration tests; each test crate uses only some of them.
+#![allow(dead_code)]
+
+use dawgdic::dawg::{Dawg, DawgBuilder};
+use dawgdic::dictionary::{Dictionary, DictionaryBuilder};
+use std::path::PathBuf;
+
+pub fn build_dawg(corpus: &[(String, u32)]) -> Dawg {
+    corpus
+        .iter()
+        .fold(DawgBuilder::new(), |mut builder, (key, value)| {
+            builder.insert_key(key, *value).unwrap();
+            builder
+        })
+        .build()
+}
+
+pub fn build_dawg_of_keys(keys: &[&str], value: u32) -> Dawg {
+    keys.iter()
+        .fold(DawgBuilder::new(), |mut builder, key| {
+            builder.insert_key(key, value).unwrap();
+            builder
+        })
+        .build()
+}
+
+pub fn build_dictionary(corpus: &[(String, u32)]) -> Dictionary {
+    DictionaryBuilder::new(build_dawg(corpus)).build()
+}
+
+pub fn load_fixture(name: &str) -> Vec<u8> {
+    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read(fixture_path).expect("Failed to read fixture")
+}
+
+pub fn load_test_corpus() -> Vec<(String, u32)> {
+    let corpus_file_path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("corpora/01_dawg_smoketest.txt");
+    std::fs::read_to_string(corpus_file_path)
+        .map(|corpus_data| {
+            corpus_data
+                .lines()
+                .map(|line| {
+                    let components = line.split('\t').collect::<Vec<&str>>();
+                    let key = components[0].to_string();
+                    let value = components[1].parse::<u32>().unwrap();
+                    (key, value)
+                })
+                .collect()
+        })
+        .expect("Failed to parse corpus")
+}
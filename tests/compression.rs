@@ -0,0 +1,59 @@
+#![cfg(feature = "compression")]
+
+mod common;
+
+use common::{build_dictionary, load_test_corpus};
+use dawgdic::dictionary::{Dictionary, DictionaryRef};
+use dawgdic::header::{self, Header};
+use std::io::Cursor;
+
+#[test]
+fn roundtrips_compressed_dictionary() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let mut compressed_buf: Vec<u8> = Vec::new();
+    dictionary.write_compressed(&mut compressed_buf).unwrap();
+
+    let header = Header::from_bytes(&compressed_buf).unwrap();
+    assert_eq!(header.flags, header::FLAG_COMPRESSED);
+    assert_eq!(header.checksum, header::checksum(&data_buf));
+    // Random values leave little beyond the byte planes for deflate to find
+    assert!(compressed_buf.len() * 8 < data_buf.len() * 5);
+
+    let new_dictionary = Dictionary::from_reader(&mut Cursor::new(&compressed_buf)).unwrap();
+    let mut new_data_buf: Vec<u8> = Vec::new();
+    new_dictionary.write(&mut new_data_buf).unwrap();
+    assert_eq!(new_data_buf, data_buf);
+
+    // Compressed units cannot be read in place
+    assert!(DictionaryRef::from_bytes(&compressed_buf).is_none());
+
+    let mut corrupted = compressed_buf.clone();
+    let last = corrupted.len() - 10;
+    corrupted[last] ^= 0x10;
+    assert!(Dictionary::from_reader(&mut Cursor::new(&corrupted)).is_none());
+    assert!(Dictionary::from_reader(&mut Cursor::new(&compressed_buf[..last])).is_none());
+}
+
+#[test]
+fn compresses_small_values_well() {
+    // Typical payloads are small ids rather than the random values of the smoketest corpus
+    let corpus: Vec<(String, u32)> = load_test_corpus()
+        .into_iter()
+        .map(|(key, _)| {
+            let value = key.len() as u32;
+            (key, value)
+        })
+        .collect();
+    let dictionary = build_dictionary(&corpus);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let mut compressed_buf: Vec<u8> = Vec::new();
+    dictionary.write_compressed(&mut compressed_buf).unwrap();
+
+    assert!(compressed_buf.len() * 5 < data_buf.len() * 3);
+}
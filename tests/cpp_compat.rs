@@ -0,0 +1,173 @@
+// Fixtures in `tests/fixtures/self_built` follow the file layouts of the C++ dawgdic
+// (`Dictionary::Write`, `Guide::Write` and `RankedGuide::Write`) for
+// `corpora/01_dawg_smoketest.txt`. They were written by this crate's builders, not by the C++
+// tool, so they only guard against changes to this crate's output.
+//
+// `matches_cpp_dawgdic_build` compares that output with the files `dawgdic-build` writes to
+// `tests/fixtures/cpp` through `tests/fixtures/generate_cpp_fixtures.sh`. It is ignored until
+// those files are checked in.
+
+mod common;
+
+use common::{build_dictionary, load_fixture, load_test_corpus};
+use dawgdic::dictionary::Dictionary;
+use dawgdic::guide::{Completer, Guide, GuideBuilder, RankedGuide, RankedGuideBuilder};
+use std::io::Cursor;
+
+#[test]
+fn writes_dictionary_in_cpp_layout() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let fixture = load_fixture("self_built/01_dawg_smoketest.dic");
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    assert_eq!(data_buf, fixture);
+
+    // `BaseType` unit count followed by the units, both little-endian
+    assert_eq!(
+        u32::from_le_bytes(fixture[..4].try_into().unwrap()),
+        dictionary.size()
+    );
+    assert_eq!(fixture.len(), 4 + 4 * dictionary.size() as usize);
+
+    let new_dictionary = Dictionary::from_reader(&mut Cursor::new(fixture)).unwrap();
+    corpus.iter().for_each(|(key, value)| {
+        assert_eq!(new_dictionary.find(key.as_bytes()), Some(*value));
+    });
+}
+
+#[test]
+fn writes_guides_in_cpp_layout() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let fixture = load_fixture("self_built/01_dawg_smoketest.guide");
+    let mut data_buf: Vec<u8> = Vec::new();
+    GuideBuilder::new(&dictionary)
+        .build()
+        .write(&mut data_buf)
+        .unwrap();
+    assert_eq!(data_buf, fixture);
+
+    // `BaseType` unit count followed by pairs of child and sibling labels
+    assert_eq!(fixture.len(), 4 + 2 * dictionary.size() as usize);
+
+    let ranked_fixture = load_fixture("self_built/01_dawg_smoketest.ranked");
+    let mut data_buf: Vec<u8> = Vec::new();
+    RankedGuideBuilder::new(&dictionary)
+        .build()
+        .write(&mut data_buf)
+        .unwrap();
+    assert_eq!(data_buf, ranked_fixture);
+}
+
+#[test]
+fn completes_keys_with_guide() {
+    let corpus = load_test_corpus();
+    let dictionary = Dictionary::from_reader(&mut Cursor::new(load_fixture(
+        "self_built/01_dawg_smoketest.dic",
+    )))
+    .unwrap();
+    let guide = Guide::from_reader(&mut Cursor::new(load_fixture(
+        "self_built/01_dawg_smoketest.guide",
+    )))
+    .unwrap();
+    assert_eq!(guide.size(), dictionary.size());
+
+    let mut completer = Completer::new(&dictionary, &guide);
+    completer.start(dictionary.root(), b"");
+    let completed: Vec<(String, u32)> = completer
+        .by_ref()
+        .map(|(key, value)| (String::from_utf8(key).unwrap(), value))
+        .collect();
+    assert_eq!(completed, corpus);
+
+    let prefix = "ac".as_bytes();
+    let index = dictionary.follow_bytes(prefix, dictionary.root()).unwrap();
+    completer.start(index, prefix);
+    let completed: Vec<(String, u32)> = completer
+        .map(|(key, value)| (String::from_utf8(key).unwrap(), value))
+        .collect();
+    let expected: Vec<(String, u32)> = corpus
+        .iter()
+        .filter(|(key, _)| key.starts_with("ac"))
+        .cloned()
+        .collect();
+    assert_eq!(completed, expected);
+}
+
+#[test]
+fn ranks_children_by_largest_value() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+    let guide = RankedGuide::from_reader(&mut Cursor::new(load_fixture(
+        "self_built/01_dawg_smoketest.ranked",
+    )))
+    .unwrap();
+
+    // Following first children leads to the key with the largest value
+    let mut index = dictionary.root();
+    let mut key = Vec::new();
+    while guide.child(index) != 0 {
+        key.push(guide.child(index));
+        index = dictionary.follow(guide.child(index), index).unwrap();
+    }
+    let (max_key, max_value) = corpus.iter().max_by_key(|(_, value)| *value).unwrap();
+    assert_eq!(key, max_key.as_bytes());
+    assert_eq!(dictionary.value(index), Some(*max_value));
+}
+
+#[test]
+fn rejects_truncated_guides() {
+    let fixture = load_fixture("self_built/01_dawg_smoketest.guide");
+    assert!(Guide::from_reader(&mut Cursor::new(&fixture[..fixture.len() - 1])).is_none());
+
+    // Unit counts far beyond the data are rejected without allocating for them
+    let huge_count = [0xFF, 0xFF, 0xFF, 0xFF, 0, 0];
+    assert!(Guide::from_reader(&mut Cursor::new(huge_count)).is_none());
+    assert!(RankedGuide::from_reader(&mut Cursor::new(huge_count)).is_none());
+}
+
+#[test]
+#[ignore = "needs tests/fixtures/cpp from generate_cpp_fixtures.sh"]
+fn matches_cpp_dawgdic_build() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let fixture = load_fixture("cpp/01_dawg_smoketest.dic");
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    assert_eq!(data_buf, fixture);
+
+    let cpp_dictionary = Dictionary::from_reader(&mut Cursor::new(&fixture)).unwrap();
+    corpus.iter().for_each(|(key, value)| {
+        assert_eq!(cpp_dictionary.find(key.as_bytes()), Some(*value));
+    });
+
+    // `dawgdic-build -g` and `-r` write the guide right after the dictionary
+    let fixture = load_fixture("cpp/01_dawg_smoketest.dic+guide");
+    let (dictionary_part, guide_part) = fixture.split_at(data_buf.len());
+    assert_eq!(dictionary_part, data_buf);
+    let mut guide_buf: Vec<u8> = Vec::new();
+    GuideBuilder::new(&dictionary)
+        .build()
+        .write(&mut guide_buf)
+        .unwrap();
+    assert_eq!(guide_part, guide_buf);
+
+    let fixture = load_fixture("cpp/01_dawg_smoketest.dic+ranked");
+    let (dictionary_part, guide_part) = fixture.split_at(data_buf.len());
+    assert_eq!(dictionary_part, data_buf);
+    let mut guide_buf: Vec<u8> = Vec::new();
+    RankedGuideBuilder::new(&dictionary)
+        .build()
+        .write(&mut guide_buf)
+        .unwrap();
+    assert_eq!(guide_part, guide_buf);
+
+    let mut reader = Cursor::new(&fixture);
+    let cpp_dictionary = Dictionary::from_reader(&mut reader).unwrap();
+    let cpp_guide = RankedGuide::from_reader(&mut reader).unwrap();
+    assert_eq!(cpp_guide.size(), cpp_dictionary.size());
+}
@@ -1,19 +1,19 @@
-use dawgdic::dawg::DawgBuilder;
-use dawgdic::dictionary::{Dictionary, DictionaryBuilder};
-use std::io::{BufRead, BufWriter, Cursor};
-use std::path::PathBuf;
+mod common;
+
+use common::{build_dawg, build_dawg_of_keys, build_dictionary, load_test_corpus};
+use dawgdic::automaton::{AlwaysMatch, Automaton};
+use dawgdic::dawg::{Dawg, DawgBuilder};
+use dawgdic::dictionary::{
+    Dictionary, DictionaryBuilder, DictionaryRef, DictionaryUnit, ValidationError,
+};
+use dawgdic::header::{self, Header};
+use std::io::{BufWriter, Cursor};
 
 #[test]
 fn creates_correct_dawg_shape() {
     let corpus = load_test_corpus();
 
-    let dawg = corpus
-        .into_iter()
-        .fold(DawgBuilder::new(), |mut builder, (key, value)| {
-            builder.insert_key(&key, value);
-            builder
-        })
-        .build();
+    let dawg = build_dawg(&corpus);
 
     assert_eq!(dawg.states_count(), 3085);
     assert_eq!(dawg.transition_count(), 4082);
@@ -26,20 +26,14 @@ fn creates_correct_dawg_shape() {
 fn creates_correct_dictionary() {
     let corpus = load_test_corpus();
 
-    let dawg = corpus
-        .iter()
-        .fold(DawgBuilder::new(), |mut builder, (key, value)| {
-            builder.insert_key(key, *value);
-            builder
-        })
-        .build();
+    let dawg = build_dawg(&corpus);
 
     let dictionary = DictionaryBuilder::new(dawg).build();
 
     // Quickly checking a couple cases
 
-    assert_eq!(dictionary.contains("this".as_bytes()), true);
-    assert_eq!(dictionary.contains("loremaster".as_bytes()), false);
+    assert!(dictionary.contains("this".as_bytes()));
+    assert!(!dictionary.contains("loremaster".as_bytes()));
 
     assert_eq!(dictionary.find("act".as_bytes()), Some(510473));
     assert_eq!(dictionary.find("annulment".as_bytes()), None);
@@ -55,18 +49,14 @@ fn creates_correct_dictionary() {
 fn serializes_and_deserializes_dictionary() {
     let corpus = load_test_corpus();
 
-    let dawg = corpus
-        .iter()
-        .fold(DawgBuilder::new(), |mut builder, (key, value)| {
-            builder.insert_key(key, *value);
-            builder
-        })
-        .build();
+    let dawg = build_dawg(&corpus);
 
     let dictionary = DictionaryBuilder::new(dawg).build();
 
     let mut data_buf: Vec<u8> = Vec::new();
-    dictionary.write(&mut BufWriter::new(&mut data_buf));
+    dictionary
+        .write(&mut BufWriter::new(&mut data_buf))
+        .unwrap();
 
     assert_eq!(data_buf.len(), 17412);
 
@@ -81,20 +71,627 @@ fn serializes_and_deserializes_dictionary() {
     })
 }
 
-fn load_test_corpus() -> Vec<(String, u32)> {
-    let corpus_file_path =
-        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("corpora/01_dawg_smoketest.txt");
-    std::fs::read_to_string(corpus_file_path)
-        .map(|corpus_data| {
-            corpus_data
-                .lines()
-                .map(|line| {
-                    let components = line.split('\t').collect::<Vec<&str>>();
-                    let key = components[0].to_string();
-                    let value = components[1].parse::<u32>().unwrap();
-                    (key, value)
-                })
-                .collect()
+#[test]
+fn serializes_and_deserializes_dawg() {
+    let corpus = load_test_corpus();
+    let dawg = build_dawg(&corpus);
+
+    let mut dawg_buf: Vec<u8> = Vec::new();
+    dawg.write(&mut dawg_buf).unwrap();
+    let new_dawg = Dawg::from_reader(&mut Cursor::new(&dawg_buf)).unwrap();
+    assert_eq!(new_dawg.states_count(), dawg.states_count());
+    assert_eq!(new_dawg.transition_count(), dawg.transition_count());
+    assert_eq!(new_dawg.merged_states_count(), dawg.merged_states_count());
+    assert_eq!(new_dawg.merging_states_count(), dawg.merging_states_count());
+    assert_eq!(
+        new_dawg.merged_transitions_count(),
+        dawg.merged_transitions_count()
+    );
+
+    let mut new_dawg_buf: Vec<u8> = Vec::new();
+    new_dawg.write(&mut new_dawg_buf).unwrap();
+    assert_eq!(new_dawg_buf, dawg_buf);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    DictionaryBuilder::new(dawg)
+        .build()
+        .write(&mut data_buf)
+        .unwrap();
+    let mut new_data_buf: Vec<u8> = Vec::new();
+    DictionaryBuilder::new(new_dawg)
+        .build()
+        .write(&mut new_data_buf)
+        .unwrap();
+    assert_eq!(new_data_buf, data_buf);
+
+    // Truncated data is rejected
+    assert!(Dawg::from_reader(&mut Cursor::new(&dawg_buf[..dawg_buf.len() - 1])).is_none());
+}
+
+#[test]
+fn serializes_dawg_with_merging_states() {
+    let keys = ["abc", "abd", "bbc", "bbd", "cd"];
+    let dawg = build_dawg_of_keys(&keys, 1);
+    assert!(dawg.merging_states_count() > 0);
+
+    let mut dawg_buf: Vec<u8> = Vec::new();
+    dawg.write(&mut dawg_buf).unwrap();
+    let new_dawg = Dawg::from_reader(&mut Cursor::new(&dawg_buf)).unwrap();
+    assert_eq!(new_dawg.merging_states_count(), dawg.merging_states_count());
+    for index in 0..=dawg.transition_count() {
+        assert_eq!(new_dawg.is_merging(index), dawg.is_merging(index));
+    }
+
+    let dictionary = DictionaryBuilder::new(new_dawg).build();
+    for key in keys {
+        assert_eq!(dictionary.find(key.as_bytes()), Some(1));
+    }
+    assert!(!dictionary.contains(b"cbc"));
+
+    // An empty DAWG survives the round trip too
+    let mut empty_buf: Vec<u8> = Vec::new();
+    DawgBuilder::new().build().write(&mut empty_buf).unwrap();
+    let empty_dawg = Dawg::from_reader(&mut Cursor::new(&empty_buf)).unwrap();
+    assert_eq!(empty_dawg.transition_count(), 0);
+    assert_eq!(DictionaryBuilder::new(empty_dawg).build().key_count(), 0);
+}
+
+#[test]
+fn builds_dictionary_with_many_merging_states() {
+    // Few distinct values let many suffixes merge
+    let corpus: Vec<(String, u32)> = load_test_corpus()
+        .into_iter()
+        .map(|(key, value)| (key, value % 3))
+        .collect();
+    let dawg = build_dawg(&corpus);
+    assert_eq!(dawg.merging_states_count(), 200);
+
+    let dictionary = DictionaryBuilder::new(dawg).build();
+    assert_eq!(dictionary.serialized_size(), 10244);
+    corpus.into_iter().for_each(|(key, value)| {
+        assert_eq!(dictionary.find(key.as_bytes()), Some(value));
+    });
+}
+
+#[test]
+fn rejects_malformed_dawgs() {
+    let dawg = build_dawg_of_keys(&["ab", "b"], 7);
+    let mut dawg_buf: Vec<u8> = Vec::new();
+    dawg.write(&mut dawg_buf).unwrap();
+    let size = dawg.transition_count() as usize + 1;
+    let base_offset = 20;
+    let label_offset = base_offset + 4 * size;
+
+    // Every transition pointing at itself or past the end
+    for index in 0..size {
+        for child in [index as u32, size as u32] {
+            let mut corrupted = dawg_buf.clone();
+            let base = u32::from_le_bytes(
+                corrupted[base_offset + 4 * index..base_offset + 4 * index + 4]
+                    .try_into()
+                    .unwrap(),
+            );
+            if index != 0 && corrupted[label_offset + index] == 0 {
+                continue;
+            }
+            let base = (child << 2) | (base & 3);
+            corrupted[base_offset + 4 * index..base_offset + 4 * index + 4]
+                .copy_from_slice(&base.to_le_bytes());
+            assert!(Dawg::from_reader(&mut Cursor::new(&corrupted)).is_none());
+        }
+    }
+
+    // Duplicate labels among siblings
+    for index in (1..size - 1).filter(|index| dawg_buf[base_offset + 4 * index] & 1 != 0) {
+        let mut corrupted = dawg_buf.clone();
+        corrupted[label_offset + index + 1] = corrupted[label_offset + index];
+        assert!(Dawg::from_reader(&mut Cursor::new(&corrupted)).is_none());
+    }
+
+    // Levels of states whose 'a' and 'b' both lead to the state below take exponential time
+    // to lay out unless those states are flagged as merging
+    let levels: u32 = 24;
+    let shared_dawg = |is_flagged: bool, b_child_offset: u32| {
+        let size = 2 * levels + 2;
+        let mut dawg_buf: Vec<u8> = Vec::new();
+        dawg_buf.extend_from_slice(&size.to_le_bytes());
+        dawg_buf.extend_from_slice(&[0; 16]);
+        let mut labels = vec![0xFF, 0];
+        dawg_buf.extend_from_slice(&((2 * levels) << 2).to_le_bytes());
+        dawg_buf.extend_from_slice(&0u32.to_le_bytes());
+        for level in 1..=levels {
+            let child = if level == 1 { 1 } else { 2 * level - 2 };
+            dawg_buf.extend_from_slice(&((child << 2) | 1).to_le_bytes());
+            dawg_buf.extend_from_slice(&((child + b_child_offset) << 2).to_le_bytes());
+            labels.extend_from_slice(b"ab");
+        }
+        dawg_buf.extend_from_slice(&labels);
+        let mut flags = vec![0; (size as usize).div_ceil(8)];
+        if is_flagged {
+            for level in 0..levels {
+                let state = if level == 0 { 1 } else { 2 * level };
+                flags[state as usize / 8] |= 1 << (state % 8);
+            }
+        }
+        dawg_buf.extend_from_slice(&flags);
+        dawg_buf
+    };
+    assert!(Dawg::from_reader(&mut Cursor::new(shared_dawg(false, 0))).is_none());
+    let flagged = Dawg::from_reader(&mut Cursor::new(shared_dawg(true, 0))).unwrap();
+    let dictionary = DictionaryBuilder::new(flagged).build();
+    assert_eq!(dictionary.find(&[b'b'; 24]), Some(0));
+    assert!(dictionary.size() < 1024);
+    // Entering a state past its first transition shares the rest of it without a flag
+    assert!(Dawg::from_reader(&mut Cursor::new(shared_dawg(true, 1))).is_none());
+
+    // Sizes far beyond the data are rejected without allocating for them
+    let mut truncated = dawg_buf.clone();
+    truncated[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Dawg::from_reader(&mut Cursor::new(&truncated)).is_none());
+    assert!(Dawg::from_reader(&mut Cursor::new(&dawg_buf[..dawg_buf.len() - 1])).is_none());
+}
+
+#[test]
+fn exports_dawg_and_dictionary_to_dot() {
+    let keys = ["abc", "abd", "bbc", "bbd", "cd"];
+    let dawg = build_dawg_of_keys(&keys, 1);
+
+    let mut dot_buf: Vec<u8> = Vec::new();
+    dawg.to_dot(&mut dot_buf).unwrap();
+    let dot = String::from_utf8(dot_buf).unwrap();
+    assert!(dot.starts_with("digraph dawg {\n"));
+    assert!(dot.ends_with("}\n"));
+    // Every transition is drawn once, either as an edge or as a value
+    let edges = dot
+        .lines()
+        .filter(|line| line.contains(" -> s") && line.contains("[label="))
+        .count();
+    let values = dot
+        .lines()
+        .filter(|line| line.contains("arrowhead=none"))
+        .count();
+    assert_eq!(edges + values, dawg.transition_count() as usize);
+    assert_eq!(values, 1);
+    let highlighted = dot
+        .lines()
+        .filter(|line| line.contains("fillcolor"))
+        .count();
+    assert_eq!(highlighted, dawg.merging_states_count() as usize);
+    assert!(dot.contains("[label=\"a\"]"));
+
+    let dictionary = DictionaryBuilder::new(dawg).build();
+    let mut dot_buf: Vec<u8> = Vec::new();
+    dictionary.to_dot(&mut dot_buf).unwrap();
+    let dot = String::from_utf8(dot_buf).unwrap();
+    assert!(dot.contains("start -> s0;"));
+    assert!(dot.contains("fillcolor"));
+    let values = dot
+        .lines()
+        .filter(|line| line.contains("arrowhead=none"))
+        .count();
+    assert_eq!(values, keys.len() - 2);
+
+    // Labels outside printable ASCII are escaped
+    let mut builder = DawgBuilder::new();
+    builder.insert_key("\"", 1).unwrap();
+    builder.insert_key("\u{e9}", 2).unwrap();
+    let dawg = builder.build();
+    let mut dot_buf: Vec<u8> = Vec::new();
+    dawg.to_dot(&mut dot_buf).unwrap();
+    let dot = String::from_utf8(dot_buf).unwrap();
+    assert!(dot.contains("[label=\"\\\"\"]"));
+    assert!(dot.contains("[label=\"0xC3\"]"));
+    assert!(dot.contains("[label=\"0xA9\"]"));
+}
+
+#[test]
+fn serializes_dictionary_with_header() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let mut legacy_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut legacy_buf).unwrap();
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write_with_header(&mut data_buf).unwrap();
+
+    assert_eq!(data_buf.len(), Header::SIZE + legacy_buf.len());
+    assert_eq!(&data_buf[Header::SIZE..], &legacy_buf[..]);
+    assert_eq!(
+        Header::from_bytes(&data_buf),
+        Some(Header {
+            version: header::VERSION,
+            flags: 0,
+            key_count: corpus.len() as u32,
+            checksum: header::checksum(&legacy_buf),
         })
-        .expect("Failed to parse corpus")
+    );
+
+    let new_dictionary = Dictionary::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    let borrowed = DictionaryRef::from_bytes(&data_buf).unwrap();
+    corpus.iter().for_each(|(key, value)| {
+        assert_eq!(new_dictionary.find(key.as_bytes()), Some(*value));
+        assert_eq!(borrowed.find(key.as_bytes()), Some(*value));
+    });
+
+    // Any corruption of the payload is caught by the checksum
+    let mut corrupted = data_buf.clone();
+    corrupted[Header::SIZE + 100] ^= 0x10;
+    assert!(Dictionary::from_reader(&mut Cursor::new(&corrupted)).is_none());
+    assert!(DictionaryRef::from_bytes(&corrupted).is_none());
+
+    // Unknown versions are rejected rather than misread
+    let mut future = data_buf.clone();
+    future[4] = 2;
+    assert!(Dictionary::from_reader(&mut Cursor::new(&future)).is_none());
+}
+
+#[test]
+fn validates_untrusted_dictionaries() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+    assert_eq!(dictionary.validate(), Ok(()));
+    assert_eq!(build_dictionary(&[]).validate(), Ok(()));
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    assert!(Dictionary::from_reader_checked(&mut Cursor::new(&data_buf)).is_some());
+    assert!(DictionaryRef::from_bytes_checked(&data_buf).is_some());
+
+    let patch_unit = |data_buf: &[u8], index: u32, patch: &dyn Fn(&mut DictionaryUnit)| {
+        let begin = 4 + index as usize * 4;
+        let mut unit = DictionaryUnit(u32::from_le_bytes(
+            data_buf[begin..begin + 4].try_into().unwrap(),
+        ));
+        patch(&mut unit);
+        let mut patched = data_buf.to_vec();
+        patched[begin..begin + 4].copy_from_slice(&unit.0.to_le_bytes());
+        patched
+    };
+
+    // Root pointing far outside of the unit array
+    let patched = patch_unit(&data_buf, 0, &|unit| {
+        unit.set_offset(1 << 20);
+    });
+    let patched_dictionary = DictionaryRef::from_bytes(&patched).unwrap();
+    assert_eq!(
+        patched_dictionary.validate(),
+        Err(ValidationError::OffsetOutOfBounds(0))
+    );
+    assert!(DictionaryRef::from_bytes_checked(&patched).is_none());
+
+    // State following the 'a' transition back into itself
+    let a_index = dictionary.follow(b'a', dictionary.root()).unwrap();
+    let patched = patch_unit(&data_buf, a_index, &|unit| {
+        unit.set_offset(u32::from(b'a'));
+    });
+    let patched_dictionary = DictionaryRef::from_bytes(&patched).unwrap();
+    assert_eq!(
+        patched_dictionary.validate(),
+        Err(ValidationError::Cycle(a_index))
+    );
+    assert!(Dictionary::from_reader_checked(&mut Cursor::new(&patched)).is_none());
+
+    assert_eq!(
+        DictionaryRef::from_bytes(&[0, 0, 0, 0]).unwrap().validate(),
+        Err(ValidationError::MissingRoot)
+    );
+
+    // Unit counts far beyond the data are rejected without allocating for them
+    let huge_count = [0xFF, 0xFF, 0xFF, 0x7F];
+    assert!(Dictionary::from_reader_checked(&mut Cursor::new(huge_count)).is_none());
+    let mut truncated = u32::MAX.to_le_bytes().to_vec();
+    truncated.extend_from_slice(&data_buf[4..]);
+    assert!(Dictionary::from_reader(&mut Cursor::new(&truncated)).is_none());
+    assert!(DictionaryRef::from_bytes(&truncated).is_none());
+}
+
+#[test]
+fn lookups_never_panic() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    assert_eq!(dictionary.find("ab".as_bytes()), None);
+    assert_eq!(dictionary.follow(b'a', u32::MAX), None);
+    assert_eq!(
+        dictionary.follow_bytes("act".as_bytes(), dictionary.size()),
+        None
+    );
+    assert_eq!(dictionary.value(u32::MAX), None);
+    assert!(!dictionary.has_value(u32::MAX));
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+
+    // State following the 'a' transition back into itself, and a root pointing nowhere
+    let a_index = dictionary.follow(b'a', dictionary.root()).unwrap() as usize;
+    let mut cyclic = data_buf.clone();
+    let mut unit = DictionaryUnit(u32::from_le_bytes(
+        cyclic[4 + a_index * 4..8 + a_index * 4].try_into().unwrap(),
+    ));
+    unit.set_offset(u32::from(b'a'));
+    cyclic[4 + a_index * 4..8 + a_index * 4].copy_from_slice(&unit.0.to_le_bytes());
+    let mut dangling = data_buf.clone();
+    dangling[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    for bytes in [&cyclic[..], &dangling[..], &[0, 0, 0, 0][..]] {
+        let broken = DictionaryRef::from_bytes(bytes).unwrap();
+        for key in ["", "a", "aaaa", "act", "this"] {
+            broken.contains(key.as_bytes());
+            broken.find(key.as_bytes());
+            broken.index_of(key.as_bytes());
+            broken.count_prefix(key.as_bytes());
+        }
+        for ordinal in [0, 1, 500, u32::MAX] {
+            broken.key_at(ordinal);
+        }
+        broken.key_count();
+        broken.search(AlwaysMatch).take(2000).count();
+        broken.range("a".."b").take(2000).count();
+        broken.stats();
+        broken.to_dot(&mut std::io::sink()).unwrap();
+    }
+}
+
+#[test]
+fn reports_dictionary_stats() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let stats = dictionary.stats();
+    assert_eq!(stats.total_units, 4352);
+    assert_eq!(stats.used_units + stats.unused_units, stats.total_units);
+    // One unit per transition of the minimized DAWG at most, shared leaves included
+    assert!(stats.used_units <= 4083);
+    assert!(stats.unused_units > 0);
+    assert_eq!(
+        stats.fill_ratio,
+        f64::from(stats.used_units) / f64::from(stats.total_units)
+    );
+    assert_eq!(stats.extension_offsets, 0);
+    assert_eq!(stats.key_count, 999);
+    assert_eq!(
+        stats.max_key_length,
+        corpus.iter().map(|(key, _)| key.len()).max().unwrap()
+    );
+    assert_eq!(stats.memory_footprint, 4352 * 4);
+
+    let empty = DictionaryBuilder::new(DawgBuilder::new().build()).build();
+    let stats = empty.stats();
+    assert_eq!(stats.key_count, 0);
+    assert_eq!(stats.max_key_length, 0);
+    assert_eq!(stats.used_units, 1);
+
+    // A root whose offset needs the extension bit
+    let mut unit = DictionaryUnit(0);
+    assert!(unit.set_offset(1 << 24));
+    assert!(unit.has_extension());
+    let mut data_buf = 1u32.to_le_bytes().to_vec();
+    data_buf.extend_from_slice(&unit.0.to_le_bytes());
+    let stats = DictionaryRef::from_bytes(&data_buf).unwrap().stats();
+    assert_eq!(stats.extension_offsets, 1);
+    assert_eq!(stats.used_units, 1);
+}
+
+#[test]
+fn accounts_for_memory_footprint() {
+    let corpus = load_test_corpus();
+
+    let mut builder = DawgBuilder::new();
+    let empty_builder_size = builder.heap_size();
+    assert_eq!(
+        builder.serialized_size(),
+        DawgBuilder::new().build().serialized_size()
+    );
+    for (key, value) in &corpus {
+        builder.insert_key(key, *value).unwrap();
+    }
+    assert!(builder.heap_size() > empty_builder_size);
+    let projected_dawg_size = builder.serialized_size();
+
+    let dawg = builder.build();
+    let mut dawg_buf: Vec<u8> = Vec::new();
+    dawg.write(&mut dawg_buf).unwrap();
+    assert_eq!(dawg.serialized_size(), dawg_buf.len());
+    // Only the last key's transitions are still unmerged before `build`
+    assert!(projected_dawg_size >= dawg.serialized_size());
+    assert!(projected_dawg_size - dawg.serialized_size() <= 5 * corpus.last().unwrap().0.len() + 6);
+    assert!(dawg.heap_size() >= (dawg.transition_count() as usize + 1) * 5);
+
+    let dawg_size = dawg.heap_size();
+    let mut dictionary_builder = DictionaryBuilder::new(dawg);
+    assert!(dictionary_builder.heap_size() >= dawg_size);
+    assert_eq!(dictionary_builder.serialized_size(), 17412);
+
+    let dictionary = dictionary_builder.build();
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    assert_eq!(dictionary.serialized_size(), 17412);
+    assert_eq!(dictionary.serialized_size(), data_buf.len());
+    let units_size = dictionary.heap_size();
+    assert!(units_size >= dictionary.size() as usize * 4);
+
+    // Headers count keys without caching the counts of every state
+    let mut header_buf: Vec<u8> = Vec::new();
+    dictionary.write_with_header(&mut header_buf).unwrap();
+    assert_eq!(
+        Header::from_bytes(&header_buf).unwrap().key_count as usize,
+        corpus.len()
+    );
+    assert_eq!(dictionary.heap_size(), units_size);
+    let borrowed = DictionaryRef::from_bytes(&data_buf).unwrap();
+    borrowed.write_with_header(&mut Vec::new()).unwrap();
+    assert_eq!(borrowed.heap_size(), 0);
+
+    // Key counts cached for ranking are accounted for once computed
+    dictionary.key_count();
+    assert!(dictionary.heap_size() > units_size);
+
+    // Borrowed units are not owned by the dictionary
+    let borrowed = DictionaryRef::from_bytes(&data_buf).unwrap();
+    assert_eq!(borrowed.heap_size(), 0);
+    assert_eq!(borrowed.serialized_size(), data_buf.len());
+}
+
+#[test]
+fn borrows_dictionary_from_bytes() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+
+    let borrowed = DictionaryRef::from_bytes(&data_buf).unwrap();
+    assert_eq!(borrowed.size(), dictionary.size());
+
+    corpus
+        .iter()
+        .enumerate()
+        .for_each(|(ordinal, (key, value))| {
+            assert_eq!(borrowed.find(key.as_bytes()), Some(*value));
+            assert_eq!(borrowed.index_of(key.as_bytes()), Some(ordinal as u32));
+        });
+    assert_eq!(borrowed.search(AlwaysMatch).count(), corpus.len());
+
+    let mut rewritten: Vec<u8> = Vec::new();
+    borrowed.write(&mut rewritten).unwrap();
+    assert_eq!(rewritten, data_buf);
+
+    assert!(DictionaryRef::from_bytes(&data_buf[..data_buf.len() - 1]).is_none());
+    assert!(DictionaryRef::from_bytes(&[]).is_none());
+}
+
+#[test]
+fn searches_dictionary_with_automaton() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let everything: Vec<(String, u32)> = dictionary
+        .search(AlwaysMatch)
+        .map(|(key, value)| (String::from_utf8(key).unwrap(), value))
+        .collect();
+    assert_eq!(everything, corpus);
+
+    // Keys of exactly four bytes drawn from a restricted alphabet
+    struct ShortWords;
+
+    impl Automaton for ShortWords {
+        type State = Option<usize>;
+
+        fn start(&self) -> Option<usize> {
+            Some(0)
+        }
+
+        fn accept(&self, state: &Option<usize>, byte: u8) -> Option<usize> {
+            state
+                .filter(|_| b"aeiostn".contains(&byte))
+                .map(|len| len + 1)
+        }
+
+        fn is_match(&self, state: &Option<usize>) -> bool {
+            *state == Some(4)
+        }
+
+        fn can_match(&self, state: &Option<usize>) -> bool {
+            state.is_some_and(|len| len <= 4)
+        }
+    }
+
+    let expected: Vec<(String, u32)> = corpus
+        .iter()
+        .filter(|(key, _)| key.len() == 4 && key.bytes().all(|b| b"aeiostn".contains(&b)))
+        .cloned()
+        .collect();
+    let found: Vec<(String, u32)> = dictionary
+        .search(ShortWords)
+        .map(|(key, value)| (String::from_utf8(key).unwrap(), value))
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn scans_key_ranges_in_order() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    let collect = |iter: dawgdic::automaton::Search<'_, _>| -> Vec<(String, u32)> {
+        iter.map(|(key, value)| (String::from_utf8(key).unwrap(), value))
+            .collect()
+    };
+    let expect = |filter: &dyn Fn(&str) -> bool| -> Vec<(String, u32)> {
+        corpus
+            .iter()
+            .filter(|(key, _)| filter(key))
+            .cloned()
+            .collect()
+    };
+
+    assert_eq!(
+        collect(dictionary.range("act".."add")),
+        expect(&|key| ("act".."add").contains(&key))
+    );
+    assert_eq!(
+        collect(dictionary.range("act"..="add")),
+        expect(&|key| ("act"..="add").contains(&key))
+    );
+    assert_eq!(
+        collect(dictionary.range("ac"..)),
+        expect(&|key| key >= "ac")
+    );
+    assert_eq!(collect(dictionary.range(..="b")), expect(&|key| key <= "b"));
+    assert_eq!(collect(dictionary.range::<&str, _>(..)), corpus);
+    assert!(dictionary.range("z".."a").next().is_none());
+}
+
+#[test]
+fn maps_keys_to_dense_ordinals() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    corpus.iter().enumerate().for_each(|(ordinal, (key, _))| {
+        assert_eq!(dictionary.index_of(key.as_bytes()), Some(ordinal as u32));
+    });
+
+    assert_eq!(dictionary.index_of("annulment".as_bytes()), None);
+    assert_eq!(dictionary.index_of("ab".as_bytes()), None);
+    assert_eq!(dictionary.index_of("".as_bytes()), None);
+
+    // Suffixes with equal values get merged into shared states
+    let merged: Vec<(String, u32)> = ["bat", "bats", "cat", "cats", "hat", "hats"]
+        .iter()
+        .map(|key| (key.to_string(), 1))
+        .collect();
+    let dictionary = build_dictionary(&merged);
+    merged.iter().enumerate().for_each(|(ordinal, (key, _))| {
+        assert_eq!(dictionary.index_of(key.as_bytes()), Some(ordinal as u32));
+    });
+}
+
+#[test]
+fn maps_ordinals_back_to_keys() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    corpus.iter().enumerate().for_each(|(ordinal, (key, _))| {
+        assert_eq!(
+            dictionary.key_at(ordinal as u32),
+            Some(key.as_bytes().to_vec())
+        );
+    });
+
+    assert_eq!(dictionary.key_at(corpus.len() as u32), None);
+}
+
+#[test]
+fn counts_keys_under_prefix() {
+    let corpus = load_test_corpus();
+    let dictionary = build_dictionary(&corpus);
+
+    for prefix in ["", "a", "ac", "act", "th", "zzz"] {
+        let expected = corpus
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .count();
+        assert_eq!(dictionary.count_prefix(prefix.as_bytes()), expected as u64);
+    }
 }
@@ -0,0 +1,24 @@
+#!/bin/sh
+# Writes the fixtures in `tests/fixtures/cpp` with `dawgdic-build` from the C++ dawgdic
+# (https://github.com/s-yata/dawgdic), for `tests/cpp_compat.rs` to compare against:
+#
+#   tests/fixtures/generate_cpp_fixtures.sh path/to/dawgdic
+#
+# `-t` reads the tab-separated values of the corpus; `-g` and `-r` append a guide and a
+# ranked guide to the dictionary.
+set -eu
+
+DAWGDIC=$(cd "$1" && pwd)
+FIXTURES=$(cd "$(dirname "$0")" && pwd)
+CORPUS=$FIXTURES/../../corpora/01_dawg_smoketest.txt
+
+cd "$DAWGDIC"
+[ -x configure ] || autoreconf -i
+[ -f Makefile ] || ./configure
+make
+BUILD=$DAWGDIC/tools/dawgdic-build
+
+mkdir -p "$FIXTURES/cpp"
+"$BUILD" -t "$CORPUS" "$FIXTURES/cpp/01_dawg_smoketest.dic"
+"$BUILD" -t -g "$CORPUS" "$FIXTURES/cpp/01_dawg_smoketest.dic+guide"
+"$BUILD" -t -r "$CORPUS" "$FIXTURES/cpp/01_dawg_smoketest.dic+ranked"
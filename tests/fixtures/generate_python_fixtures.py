@@ -0,0 +1,18 @@
+#!/usr/bin/env python3
+"""Writes the fixtures in tests/fixtures/python with the Python `DAWG` package
+(https://github.com/pytries/DAWG, `pip install DAWG`), for tests/python_dawg.rs to read.
+"""
+
+import os
+
+import dawg
+
+python_dir = os.path.join(os.path.dirname(os.path.abspath(__file__)), "python")
+os.makedirs(python_dir, exist_ok=True)
+
+dawg.CompletionDAWG(["f", "foo", "foobar", "bar"]).save(
+    os.path.join(python_dir, "completion.dawg")
+)
+dawg.BytesDAWG(
+    [("bar", b"\x00\xff\x10"), ("foo", b"f"), ("foo", b"foo"), ("foobar", b"")]
+).save(os.path.join(python_dir, "bytes.dawg"))
@@ -0,0 +1,214 @@
+mod common;
+
+use common::{build_dawg, load_test_corpus};
+use dawgdic::automaton::AlwaysMatch;
+use dawgdic::dictionary::{
+    AutoDictionary, Dictionary, DictionaryBuilder, DictionaryRef, DictionaryUnit, LargeDictionary,
+    Unit,
+};
+use dawgdic::guide::{Completer, GuideBuilder};
+use dawgdic::header::{self, Header};
+use dawgdic::unit::BaseType;
+use std::io::Cursor;
+
+#[test]
+fn builds_large_dictionary() {
+    let corpus = load_test_corpus();
+    let dictionary = DictionaryBuilder::new(build_dawg(&corpus)).build_large();
+    let compact = DictionaryBuilder::new(build_dawg(&corpus)).build();
+
+    assert_eq!(dictionary.size(), compact.size());
+    assert_eq!(dictionary.key_count(), corpus.len() as u32);
+    assert!(dictionary.validate().is_ok());
+    for (key, value) in &corpus {
+        assert_eq!(dictionary.find(key.as_bytes()), Some(*value));
+    }
+    assert!(!dictionary.contains(b"loremaster"));
+    assert_eq!(
+        dictionary.serialized_size(),
+        Header::SIZE + compact.serialized_size() * 2 - 4
+    );
+
+    let keys: Vec<(Vec<u8>, u32)> = dictionary.search(AlwaysMatch).collect();
+    let expected: Vec<(Vec<u8>, u32)> = corpus
+        .iter()
+        .map(|(key, value)| (key.as_bytes().to_vec(), *value))
+        .collect();
+    assert_eq!(keys, expected);
+
+    let guide = GuideBuilder::new(&dictionary).build();
+    let mut completer = Completer::new(&dictionary, &guide);
+    let index = dictionary.follow_bytes(b"act", dictionary.root()).unwrap();
+    completer.start(index, b"act");
+    let completions: Vec<Vec<u8>> = completer.take(2).map(|(key, _)| key).collect();
+    assert_eq!(completions, vec![b"act".to_vec(), b"action".to_vec()]);
+}
+
+#[test]
+fn roundtrips_large_dictionary() {
+    let corpus = load_test_corpus();
+    let dictionary = DictionaryBuilder::new(build_dawg(&corpus)).build_large();
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write_with_header(&mut data_buf).unwrap();
+    let header = Header::from_bytes(&data_buf).unwrap();
+    assert_eq!(header.flags, header::FLAG_LARGE_UNITS);
+
+    let new_dictionary = LargeDictionary::from_large_reader(&mut Cursor::new(&data_buf)).unwrap();
+    let mut new_data_buf: Vec<u8> = Vec::new();
+    new_dictionary.write_with_header(&mut new_data_buf).unwrap();
+    assert_eq!(new_data_buf, data_buf);
+
+    let auto = AutoDictionary::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert!(auto.is_large());
+    assert_eq!(auto.find(b"act"), Some(510473));
+
+    // Readers of 32-bit units must not misread 64-bit ones
+    assert!(Dictionary::from_reader(&mut Cursor::new(&data_buf)).is_none());
+    assert!(DictionaryRef::from_bytes(&data_buf).is_none());
+    let mut corrupted = data_buf.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0x10;
+    assert!(LargeDictionary::from_large_reader(&mut Cursor::new(&corrupted)).is_none());
+}
+
+#[test]
+fn writes_large_dictionary_with_header() {
+    let corpus = load_test_corpus();
+    let dictionary = DictionaryBuilder::new(build_dawg(&corpus)).build_large();
+
+    // There is no headerless format for 64-bit units
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    assert_eq!(data_buf.len(), dictionary.serialized_size());
+    let mut header_buf: Vec<u8> = Vec::new();
+    dictionary.write_with_header(&mut header_buf).unwrap();
+    assert_eq!(data_buf, header_buf);
+
+    assert!(Dictionary::from_reader(&mut Cursor::new(&data_buf)).is_none());
+    assert!(DictionaryRef::from_bytes(&data_buf).is_none());
+    let auto = AutoDictionary::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert!(auto.is_large());
+    assert_eq!(auto.find(b"act"), Some(510473));
+}
+
+#[test]
+fn falls_back_to_large_units_when_offsets_overflow() {
+    let corpus = load_test_corpus();
+    let dictionary = DictionaryBuilder::<NarrowUnit>::with_layout(build_dawg(&corpus)).build_auto();
+    assert!(dictionary.is_large());
+    assert_eq!(dictionary.key_count(), corpus.len() as u32);
+    for (key, value) in &corpus {
+        assert_eq!(dictionary.find(key.as_bytes()), Some(*value));
+    }
+
+    let dictionary = DictionaryBuilder::new(build_dawg(&corpus)).build_auto();
+    assert!(!dictionary.is_large());
+}
+
+// `DictionaryUnit` storing offsets below 1024 only, as if its offset field were 10 bits wide,
+// which the corpus overflows
+#[derive(Copy, Clone, Default)]
+struct NarrowUnit(DictionaryUnit);
+
+const NARROW_OFFSET_LIMIT: BaseType = 1024;
+
+impl Unit for NarrowUnit {
+    const SIZE: usize = DictionaryUnit::SIZE;
+
+    const HEADER_FLAGS: u32 = DictionaryUnit::HEADER_FLAGS;
+
+    fn fits_offset(offset: BaseType) -> bool {
+        offset < NARROW_OFFSET_LIMIT
+    }
+
+    fn to_bits(&self) -> u64 {
+        self.0.to_bits()
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        NarrowUnit(DictionaryUnit::from_bits(bits))
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.0.is_leaf()
+    }
+
+    fn has_leaf(&self) -> bool {
+        self.0.has_leaf()
+    }
+
+    fn set_has_leaf(&mut self) {
+        self.0.set_has_leaf()
+    }
+
+    fn value(&self) -> BaseType {
+        self.0.value()
+    }
+
+    fn set_value(&mut self, value: BaseType) {
+        self.0.set_value(value)
+    }
+
+    fn label(&self) -> BaseType {
+        self.0.label()
+    }
+
+    fn set_label(&mut self, value: BaseType) {
+        self.0.set_label(value)
+    }
+
+    fn has_extension(&self) -> bool {
+        self.0.has_extension()
+    }
+
+    fn offset(&self) -> BaseType {
+        self.0.offset()
+    }
+
+    fn set_offset(&mut self, offset: BaseType) -> bool {
+        offset < NARROW_OFFSET_LIMIT && self.0.set_offset(offset)
+    }
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn roundtrips_compressed_large_dictionary() {
+    let corpus = load_test_corpus();
+    let dictionary = DictionaryBuilder::new(build_dawg(&corpus)).build_large();
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write_compressed(&mut data_buf).unwrap();
+    let header = Header::from_bytes(&data_buf).unwrap();
+    assert_eq!(
+        header.flags,
+        header::FLAG_COMPRESSED | header::FLAG_LARGE_UNITS
+    );
+
+    let new_dictionary = LargeDictionary::from_large_reader(&mut Cursor::new(&data_buf)).unwrap();
+    let mut units_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut units_buf).unwrap();
+    let mut new_units_buf: Vec<u8> = Vec::new();
+    new_dictionary.write(&mut new_units_buf).unwrap();
+    assert_eq!(new_units_buf, units_buf);
+    assert!(Dictionary::from_reader(&mut Cursor::new(&data_buf)).is_none());
+}
+
+#[test]
+fn builds_compact_dictionary_when_offsets_fit() {
+    let corpus = load_test_corpus();
+    let dictionary = DictionaryBuilder::new(build_dawg(&corpus)).build_auto();
+    assert!(!dictionary.is_large());
+    assert_eq!(dictionary.key_count(), corpus.len() as u32);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write_with_header(&mut data_buf).unwrap();
+    let compact = Dictionary::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert_eq!(compact.find(b"act"), Some(510473));
+
+    let mut legacy_buf: Vec<u8> = Vec::new();
+    compact.write(&mut legacy_buf).unwrap();
+    let auto = AutoDictionary::from_reader(&mut Cursor::new(&legacy_buf)).unwrap();
+    assert!(!auto.is_large());
+    assert_eq!(auto.size(), compact.size());
+}
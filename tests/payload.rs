@@ -0,0 +1,122 @@
+mod common;
+
+use common::load_test_corpus;
+use dawgdic::payload::{PayloadDawgBuilder, PayloadDictionary, TABLE_BIT};
+use std::collections::HashSet;
+use std::io::Cursor;
+
+#[test]
+fn stores_u64_values() {
+    let corpus = load_test_corpus();
+    // Spread values over the whole range, so that most go to the side table
+    let payload_corpus: Vec<(String, u64)> = corpus
+        .iter()
+        .map(|(key, value)| (key.clone(), u64::from(*value) << (value % 40)))
+        .collect();
+    let dictionary = build_payload_dictionary(&payload_corpus);
+
+    let table_len = payload_corpus
+        .iter()
+        .map(|(_, value)| *value)
+        .filter(|value| *value >= u64::from(TABLE_BIT))
+        .collect::<HashSet<u64>>()
+        .len();
+    assert!(table_len > 0 && table_len < payload_corpus.len());
+    assert_eq!(dictionary.table_len(), table_len);
+    for (key, value) in &payload_corpus {
+        assert_eq!(dictionary.find(key.as_bytes()), Some(*value));
+    }
+    assert_eq!(dictionary.find(b"loremaster"), None);
+    assert_eq!(dictionary.iter().collect::<Vec<_>>().len(), corpus.len());
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let new_dictionary =
+        PayloadDictionary::<u64>::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert_eq!(
+        new_dictionary.iter().collect::<Vec<_>>(),
+        dictionary.iter().collect::<Vec<_>>()
+    );
+    assert!(PayloadDictionary::<u64>::from_reader(&mut Cursor::new(
+        &data_buf[..data_buf.len() - 1]
+    ))
+    .is_none());
+}
+
+#[test]
+fn stores_arbitrary_payloads() {
+    let corpus = load_test_corpus();
+    let payload_corpus: Vec<(String, String)> = corpus
+        .iter()
+        .map(|(key, value)| (key.clone(), format!("{}={}", key, value)))
+        .collect();
+    let dictionary = build_payload_dictionary(&payload_corpus);
+    assert_eq!(dictionary.table_len(), corpus.len());
+    assert_eq!(dictionary.find(b"act"), Some("act=510473".to_string()));
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let new_dictionary =
+        PayloadDictionary::<String>::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    for (key, value) in &payload_corpus {
+        assert_eq!(new_dictionary.find(key.as_bytes()).as_ref(), Some(value));
+    }
+}
+
+#[test]
+fn shares_equal_payloads() {
+    let corpus = load_test_corpus();
+    // Few distinct tag sets spread over every key, as with morphological tags
+    let shared_corpus: Vec<(String, Vec<u8>)> = corpus
+        .iter()
+        .map(|(key, value)| (key.clone(), format!("tags {}", value % 5).into_bytes()))
+        .collect();
+    let dictionary = build_payload_dictionary(&shared_corpus);
+    assert_eq!(dictionary.table_len(), 5);
+    for (key, value) in &shared_corpus {
+        assert_eq!(dictionary.find(key.as_bytes()).as_ref(), Some(value));
+    }
+
+    let unique_corpus: Vec<(String, Vec<u8>)> = corpus
+        .iter()
+        .map(|(key, value)| (key.clone(), format!("tags {}", value).into_bytes()))
+        .collect();
+    let unique_dictionary = build_payload_dictionary(&unique_corpus);
+    assert!(dictionary.dictionary().size() < unique_dictionary.dictionary().size());
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let mut unique_data_buf: Vec<u8> = Vec::new();
+    unique_dictionary.write(&mut unique_data_buf).unwrap();
+    assert!(data_buf.len() * 2 < unique_data_buf.len());
+
+    let new_dictionary =
+        PayloadDictionary::<Vec<u8>>::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert_eq!(new_dictionary.table_len(), 5);
+    assert_eq!(
+        new_dictionary.iter().collect::<Vec<_>>(),
+        dictionary.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn rejects_unsorted_keys_without_growing_table() {
+    let mut builder = PayloadDawgBuilder::new();
+    builder.insert_key("b", u64::MAX).unwrap();
+    assert!(builder.insert_key("a", u64::MAX).is_err());
+    let dictionary = builder.build();
+    assert_eq!(dictionary.table_len(), 1);
+    assert_eq!(dictionary.find(b"b"), Some(u64::MAX));
+}
+
+fn build_payload_dictionary<P: dawgdic::payload::Payload>(
+    corpus: &[(String, P)],
+) -> PayloadDictionary<P> {
+    corpus
+        .iter()
+        .fold(PayloadDawgBuilder::new(), |mut builder, (key, payload)| {
+            builder.insert_key(key, payload.clone()).unwrap();
+            builder
+        })
+        .build()
+}
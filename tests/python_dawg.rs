@@ -0,0 +1,117 @@
+mod common;
+
+use common::{build_dictionary, load_fixture};
+use dawgdic::guide::GuideBuilder;
+use dawgdic::python::{BytesDawg, CompletionDawg};
+use std::io::Cursor;
+
+// Files here are written by this crate's builders the way the Python `DAWG` package writes
+// them. The ignored tests read files saved by the package itself to `tests/fixtures/python`,
+// which `tests/fixtures/generate_python_fixtures.py` generates; they run once those files are
+// checked in.
+
+// Serializes keys the way `CompletionDAWG.write` does: the dictionary, then the guide
+fn write_completion_dawg(keys: &[(String, u32)]) -> Vec<u8> {
+    let mut keys = keys.to_vec();
+    keys.sort();
+    let dictionary = build_dictionary(&keys);
+    let guide = GuideBuilder::new(&dictionary).build();
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    guide.write(&mut data_buf).unwrap();
+    data_buf
+}
+
+// Keys of `BytesDAWG([("bar", b"\x00\xff\x10"), ("foo", b"f"), ("foo", b"foo"), ("foobar", b"")])`,
+// encoded with `binascii.b2a_base64` like the package does
+fn bytes_dawg_keys() -> Vec<(String, u32)> {
+    [
+        "bar\u{1}AP8Q\n",
+        "foo\u{1}Zg==\n",
+        "foo\u{1}Zm9v\n",
+        "foobar\u{1}\n",
+    ]
+    .iter()
+    .map(|key| (key.to_string(), 0))
+    .collect()
+}
+
+#[test]
+fn reads_completion_dawg() {
+    let keys: Vec<(String, u32)> = ["f", "foo", "foobar", "bar"]
+        .iter()
+        .enumerate()
+        .map(|(value, key)| (key.to_string(), value as u32))
+        .collect();
+    let data_buf = write_completion_dawg(&keys);
+
+    let dawg = CompletionDawg::from_reader(&mut Cursor::new(data_buf)).unwrap();
+    assert!(dawg.contains(b"foo"));
+    assert!(!dawg.contains(b"fo"));
+
+    let completed: Vec<(Vec<u8>, u32)> = dawg.complete(b"fo").collect();
+    assert_eq!(
+        completed,
+        vec![(b"foo".to_vec(), 1), (b"foobar".to_vec(), 2)]
+    );
+    assert_eq!(dawg.complete(b"x").count(), 0);
+}
+
+#[test]
+fn reads_bytes_dawg() {
+    let data_buf = write_completion_dawg(&bytes_dawg_keys());
+
+    let dawg = BytesDawg::from_reader(&mut Cursor::new(data_buf)).unwrap();
+    assert!(dawg.contains(b"foo"));
+    assert!(dawg.contains(b"foobar"));
+    assert!(!dawg.contains(b"fo"));
+
+    assert_eq!(dawg.get(b"bar"), vec![vec![0x00, 0xFF, 0x10]]);
+    assert_eq!(dawg.get(b"foo"), vec![b"f".to_vec(), b"foo".to_vec()]);
+    assert_eq!(dawg.get(b"foobar"), vec![Vec::<u8>::new()]);
+    assert!(dawg.get(b"fo").is_empty());
+
+    let items: Vec<(Vec<u8>, Vec<u8>)> = dawg.items(b"foo").collect();
+    assert_eq!(
+        items,
+        vec![
+            (b"foo".to_vec(), b"f".to_vec()),
+            (b"foo".to_vec(), b"foo".to_vec()),
+            (b"foobar".to_vec(), b"".to_vec()),
+        ]
+    );
+}
+
+#[test]
+#[ignore = "needs tests/fixtures/python from generate_python_fixtures.py"]
+fn reads_completion_dawg_saved_by_python() {
+    let fixture = load_fixture("python/completion.dawg");
+
+    let dawg = CompletionDawg::from_reader(&mut Cursor::new(&fixture)).unwrap();
+    assert!(dawg.contains(b"foo"));
+    assert!(!dawg.contains(b"fo"));
+    let completed: Vec<Vec<u8>> = dawg.complete(b"fo").map(|(key, _)| key).collect();
+    assert_eq!(completed, vec![b"foo".to_vec(), b"foobar".to_vec()]);
+
+    // `CompletionDAWG` stores no values
+    let keys: Vec<(String, u32)> = ["f", "foo", "foobar", "bar"]
+        .iter()
+        .map(|key| (key.to_string(), 0))
+        .collect();
+    assert_eq!(write_completion_dawg(&keys), fixture);
+}
+
+#[test]
+#[ignore = "needs tests/fixtures/python from generate_python_fixtures.py"]
+fn reads_bytes_dawg_saved_by_python() {
+    let fixture = load_fixture("python/bytes.dawg");
+
+    let dawg = BytesDawg::from_reader(&mut Cursor::new(&fixture)).unwrap();
+    assert_eq!(dawg.get(b"bar"), vec![vec![0x00, 0xFF, 0x10]]);
+    assert_eq!(dawg.get(b"foo"), vec![b"f".to_vec(), b"foo".to_vec()]);
+    assert_eq!(dawg.get(b"foobar"), vec![Vec::<u8>::new()]);
+    assert!(dawg.get(b"fo").is_empty());
+
+    assert_eq!(write_completion_dawg(&bytes_dawg_keys()), fixture);
+}
@@ -0,0 +1,55 @@
+use dawgdic::bytes_dictionary::BytesDictionaryBuilder;
+use dawgdic::record_dictionary::{Record, RecordDictionary, RecordDictionaryBuilder};
+use std::io::Cursor;
+
+// Lemma id, part of speech tag and frequency
+type Entry = (u32, u8, u16);
+
+#[test]
+fn maps_keys_to_typed_records() {
+    let mut builder = RecordDictionaryBuilder::<Entry>::new();
+    builder.insert("стали".as_bytes(), &(12, 2, 310)).unwrap();
+    builder.insert("стали".as_bytes(), &(7, 1, 45)).unwrap();
+    builder.insert("сталь".as_bytes(), &(7, 1, 1200)).unwrap();
+    assert!(builder.insert(b"a\x01b", &(1, 1, 1)).is_err());
+    let dictionary = builder.build();
+
+    let mut records = dictionary.get("стали".as_bytes());
+    records.sort();
+    assert_eq!(records, vec![(7, 1, 45), (12, 2, 310)]);
+    assert_eq!(dictionary.get("сталь".as_bytes()), vec![(7, 1, 1200)]);
+    assert!(dictionary.get("стал".as_bytes()).is_empty());
+    assert!(dictionary.contains("сталь".as_bytes()));
+    assert_eq!(dictionary.items("стал".as_bytes()).count(), 3);
+
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    let new_dictionary =
+        RecordDictionary::<Entry>::from_reader(&mut Cursor::new(&data_buf)).unwrap();
+    assert_eq!(
+        new_dictionary.items(b"").collect::<Vec<_>>(),
+        dictionary.items(b"").collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn encodes_records_like_struct_pack() {
+    // `struct.pack(">IHH", 1, 2, 3)`
+    let mut bytes = Vec::new();
+    (1u32, 2u16, 3u16).encode(&mut bytes);
+    assert_eq!(bytes, vec![0, 0, 0, 1, 0, 2, 0, 3]);
+    assert_eq!(<(u32, u16, u16)>::SIZE, 8);
+    assert_eq!(<(u32, u16, u16)>::decode(&bytes), Some((1, 2, 3)));
+    assert_eq!(<(u32, u16, u16)>::decode(&bytes[1..]), None);
+    assert_eq!(<(f64,)>::decode(&1.5f64.to_be_bytes()), Some((1.5,)));
+}
+
+#[test]
+fn skips_values_of_another_size() {
+    let mut builder = BytesDictionaryBuilder::new();
+    builder.insert(b"key", &[0, 0, 0, 5]).unwrap();
+    builder.insert(b"key", &[0, 5]).unwrap();
+    let dictionary = RecordDictionary::<u32>::from_bytes_dictionary(builder.build());
+    assert_eq!(dictionary.get(b"key"), vec![5]);
+    assert_eq!(dictionary.bytes_dictionary().get(b"key").len(), 2);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::{build_dawg_of_keys, build_dictionary};
+use dawgdic::dawg::Dawg;
+use dawgdic::dictionary::{Dictionary, DictionaryBuilder};
+use dawgdic::guide::{Guide, GuideBuilder, RankedGuide, RankedGuideBuilder};
+
+#[test]
+fn roundtrips_dictionary_and_guides_through_bincode() {
+    let keys = ["apple", "apply", "banana", "band", "bandana"];
+    let corpus: Vec<(String, u32)> = keys
+        .iter()
+        .enumerate()
+        .map(|(value, key)| (key.to_string(), value as u32))
+        .collect();
+    let dictionary = build_dictionary(&corpus);
+    let guide = GuideBuilder::new(&dictionary).build();
+    let ranked_guide = RankedGuideBuilder::new(&dictionary).build();
+
+    let encoded = bincode::serialize(&(&dictionary, &guide, &ranked_guide)).unwrap();
+
+    // Units are stored as a byte string, without per-unit overhead
+    let mut data_buf: Vec<u8> = Vec::new();
+    dictionary.write(&mut data_buf).unwrap();
+    guide.write(&mut data_buf).unwrap();
+    ranked_guide.write(&mut data_buf).unwrap();
+    assert_eq!(encoded.len(), data_buf.len() + 3 * 8);
+
+    let (new_dictionary, new_guide, new_ranked_guide): (Dictionary, Guide, RankedGuide) =
+        bincode::deserialize(&encoded).unwrap();
+    keys.iter().enumerate().for_each(|(value, key)| {
+        assert_eq!(new_dictionary.find(key.as_bytes()), Some(value as u32));
+    });
+    assert_eq!(new_guide.size(), guide.size());
+    assert_eq!(new_ranked_guide.size(), ranked_guide.size());
+
+    let encoded = bincode::serialize(&dictionary).unwrap();
+    assert!(bincode::deserialize::<Dictionary>(&encoded[..encoded.len() - 1]).is_err());
+
+    // A byte string holding more than a dictionary is rejected too
+    let mut padded = data_buf[..dictionary.size() as usize * 4 + 4].to_vec();
+    padded.push(0);
+    let encoded = bincode::serialize(&serde_bytes_like(&padded)).unwrap();
+    assert!(bincode::deserialize::<Dictionary>(&encoded).is_err());
+}
+
+// Serializes as a byte string, like `Dictionary` itself does
+fn serde_bytes_like(bytes: &[u8]) -> impl serde::Serialize + '_ {
+    struct Bytes<'a>(&'a [u8]);
+    impl serde::Serialize for Bytes<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+    Bytes(bytes)
+}
+
+#[test]
+fn roundtrips_dawg_through_bincode() {
+    let keys = ["apple", "apply", "banana", "band", "bandana"];
+    let dawg = build_dawg_of_keys(&keys, 1);
+
+    let encoded = bincode::serialize(&dawg).unwrap();
+    let new_dawg: Dawg = bincode::deserialize(&encoded).unwrap();
+    assert_eq!(new_dawg.transition_count(), dawg.transition_count());
+    assert_eq!(new_dawg.merging_states_count(), dawg.merging_states_count());
+
+    let dictionary = DictionaryBuilder::new(new_dawg).build();
+    keys.iter().for_each(|key| {
+        assert_eq!(dictionary.find(key.as_bytes()), Some(1));
+    });
+
+    assert!(bincode::deserialize::<Dawg>(&encoded[..encoded.len() - 1]).is_err());
+}